@@ -1,13 +1,15 @@
 #![cfg(feature = "database")]
 use crate::commands::price::Coins;
-use crate::{Context, Error, DB};
-use poise::{CreateReply, Modal};
-use serde::{Deserialize, Serialize};
+use crate::settings::commonfunctions::{findlookalike, guildcoins, ownercheck};
+use crate::settings::dbstructs::TokenRequest;
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::tokenstore::{store, StoredToken, TokenStore};
+use crate::{dexscreener, tokenindex};
+use crate::{Error, DB};
+use chrono::Utc;
+use poise::Modal;
 use serenity::all::GuildId;
-use serenity::{
-    all::{CreateActionRow, CreateButton, CreateEmbed},
-    model::application::ComponentInteraction,
-};
+use serenity::all::{CreateActionRow, CreateButton};
 
 #[derive(Debug, Modal, Clone)]
 #[name = "Add a custom token"]
@@ -22,139 +24,165 @@ struct AddToken {
     logo: Option<String>,
 }
 
+/// Add a token to the guild, or to all guilds.
+/*
+1. Show the tokens of the guild and let the author open the modal with the address
+2. Look the address up on Dexscreener and block lookalikes of global or verified tokens
+3. Let the author confirm the token for this guild or all guilds, or request it to be global
+*/
 #[allow(clippy::too_many_lines)]
 pub async fn addtoken(
-    ctx: Context<'_>,
-    interaction: ComponentInteraction,
+    menu: &mut SettingsMenu<'_>,
     guildid: GuildId,
     globaltokenpermission: bool,
 ) -> Result<(), Error> {
-    println!("Trying to open modal");
-    let modalresponse = match poise::execute_modal_on_component_interaction::<AddToken>(
-        ctx.serenity_context(),
-        interaction,
-        None,
-        None,
-    )
-    .await?
-    {
-        Some(val) => val,
-        None => {
-            ctx.say("No datat found in modal").await?;
-            return Ok(());
-        }
+    let ctx = menu.ctx;
+    let mut tokennames: Vec<String> = guildcoins(guildid)
+        .await?
+        .into_iter()
+        .map(|coin| coin.name)
+        .collect();
+    tokennames.sort();
+    let currenttokens = if tokennames.is_empty() {
+        "No tokens are added yet".to_string()
+    } else {
+        format!("Tokens available in /price: {}", tokennames.join(", "))
     };
 
-    let url = format!(
-        "https://api.dexscreener.com/latest/dex/tokens/{}",
-        modalresponse.address
-    );
-    let client = reqwest::Client::new();
-    let result = client.get(url).send().await?;
-    let parsedresult = result.json::<Root>().await?;
-    let basetoken = parsedresult.pairs[0].base_token.clone();
+    let addressbuttonid = format!("addressbutton-{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            currenttokens,
+            vec![CreateActionRow::Buttons(vec![CreateButton::new(
+                addressbuttonid,
+            )
+            .label("Enter address")
+            .style(serenity::all::ButtonStyle::Primary)])],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let Some(modalresponse) = menu.modal::<AddToken>(interaction).await? else {
+        return Ok(());
+    };
+
+    let Some(pair) = dexscreener::bestpair(&modalresponse.address).await? else {
+        menu.finish("This token is not available on Dexscreener")
+            .await?;
+        return Ok(());
+    };
+    let (basetoken, chain) = (pair.base_token, pair.chain_id);
 
-    let mut embed = CreateEmbed::default()
-        .title("Check if the following information is correct:")
-        .field("Symbol", basetoken.symbol.clone(), false)
-        .field("Address", basetoken.address.clone(), false);
+    // Block tokens that copy the symbol or name of a global or verified token, unless the owner adds them
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let lookalike = findlookalike(
+        &coins,
+        &basetoken.symbol,
+        &basetoken.name,
+        &basetoken.address,
+    );
+    if let Some(lookalike) = lookalike {
+        if !ownercheck(ctx, None).await? {
+            menu.finish(format!(
+                "{} can not be added, because it uses the name of {} with a different address ({}). Ask the bot owner to add it if it is not an impersonation.",
+                basetoken.symbol, lookalike.name, lookalike.address
+            ))
+            .await?;
+            return Ok(());
+        }
+    }
 
+    let mut lines = vec![
+        "Check if the following information is correct:".to_string(),
+        format!("**Symbol**: {}", basetoken.symbol),
+        format!("**Address**: {}", basetoken.address),
+    ];
+    if let Some(lookalike) = lookalike {
+        lines.push(format!(
+            "⚠️ **Possible impersonation**: {} already exists with address {}. Only add this token if you are sure it is not a copy.",
+            lookalike.name, lookalike.address
+        ));
+    }
     if let Some(logourl) = modalresponse.logo {
-        embed = embed.thumbnail(logourl);
+        lines.push(format!("**Logo**: {logourl}"));
     }
+    let content = lines.join("\n");
+
     let guildbuttonid = format!("guildbutton-{}", ctx.id());
-    let guildbutton = CreateButton::new(guildbuttonid.clone())
+    let guildbutton = CreateButton::new(guildbuttonid)
         .label("Add token in this server")
         .style(serenity::all::ButtonStyle::Primary);
     let globalbuttonid = format!("globalbutton-{}", ctx.id());
     let globalbutton = CreateButton::new(globalbuttonid.clone())
-        .label("Add token in all servers")
+        .label(if globaltokenpermission {
+            "Add token in all servers"
+        } else {
+            "Request to add token in all servers"
+        })
         .style(serenity::all::ButtonStyle::Danger);
-    let actionrow = CreateActionRow::Buttons(vec![guildbutton.clone(), globalbutton.clone()]);
-    let reply = CreateReply::default()
-        .embed(embed)
-        .components(vec![actionrow])
-        .ephemeral(true);
-    let replyhandle = ctx.send(reply.clone()).await?;
-
-    let message = match replyhandle.clone().into_message().await {
-        Ok(message) => message,
-        Err(_) => {
-            replyhandle
-                .edit(
-                    ctx,
-                    reply.components(vec![CreateActionRow::Buttons(vec![
-                        guildbutton,
-                        globalbutton,
-                    ])]),
-                )
-                .await?;
-            return Ok(());
-        }
+    let Some(interaction) = menu
+        .page(
+            content,
+            vec![CreateActionRow::Buttons(vec![guildbutton, globalbutton])],
+        )
+        .await?
+    else {
+        return Ok(());
     };
-    match message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 2))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![guildbuttonid.clone(), globalbuttonid.clone()])
-        .await
-    {
-        Some(val) => {
-            if !globaltokenpermission && val.data.custom_id.eq(&globalbuttonid) {
-                ctx.send(
-                    CreateReply::default()
-                        .content("This guild does not have the permission to set coins globally"),
-                )
-                .await?;
-                return Ok(());
-            }
+    let global = interaction.data.custom_id == globalbuttonid;
 
-            let _: Option<Coins> = DB
-                .create(("Coins", basetoken.symbol.clone()))
-                .content(Coins {
-                    name: basetoken.symbol,
-                    address: basetoken.address,
-                    guildid,
-                    global: val.data.custom_id.eq(&globalbuttonid),
-                })
-                .await?;
-            message.delete(ctx).await?;
-        }
-        None => {
-            replyhandle
-                .edit(
-                    ctx,
-                    reply.components(vec![CreateActionRow::Buttons(vec![
-                        guildbutton.disabled(true),
-                        globalbutton.disabled(true),
-                    ])]),
-                )
-                .await?;
+    // Guilds without the permission to add global tokens send a request to the bot owner instead
+    if !globaltokenpermission && global {
+        let pending: Option<TokenRequest> = DB
+            .select(("tokenrequests", basetoken.address.to_lowercase()))
+            .await?;
+        if let Some(pending) = pending {
+            menu.finish(format!(
+                "{} was already requested <t:{}:R> and is waiting for the bot owner to review it",
+                pending.name, pending.requestedat
+            ))
+            .await?;
             return Ok(());
         }
+        let _: Option<TokenRequest> = DB
+            .create(("tokenrequests", basetoken.address.to_lowercase()))
+            .content(TokenRequest {
+                name: basetoken.symbol.clone(),
+                address: basetoken.address,
+                guildid,
+                channelid: ctx.channel_id(),
+                requester: ctx.author().id,
+                requestedat: Utc::now().timestamp(),
+                chain: Some(chain),
+            })
+            .await?;
+        menu.finish(format!(
+            "This guild can not add tokens to all servers, so {} is sent to the bot owner for review. The result will be posted in this channel.",
+            basetoken.symbol
+        ))
+        .await?;
+        return Ok(());
     }
 
-    Ok(())
-}
-
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Root {
-    pub schema_version: String,
-    pub pairs: Vec<Pair>,
-}
-
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Pair {
-    pub url: String,
-    pub base_token: BaseToken,
-}
+    let symbol = basetoken.symbol.clone();
+    store()
+        .insert(StoredToken {
+            name: basetoken.symbol,
+            address: basetoken.address,
+            guildid: Some(guildid),
+            global,
+            verified: false,
+            chain: Some(chain),
+        })
+        .await?;
+    tokenindex::tryrefresh().await;
+    menu.finish(if global {
+        format!("Added {symbol} in all servers")
+    } else {
+        format!("Added {symbol} in this server")
+    })
+    .await?;
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BaseToken {
-    pub address: String,
-    pub name: String,
-    pub symbol: String,
+    Ok(())
 }
@@ -0,0 +1,463 @@
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::commands::tokens::checkfiletoken;
+use crate::settings::commonfunctions::{administratorcheck, botownercheck, getguildid, ownercheck};
+use crate::settings::dbstructs::{
+    AutoDetect, AvailableSettings, AvailableSlashcommands, BoostFeed, Digest, GuildCommands,
+    GuildPermissions, MarketAlerts, PairFeed, StatChannel, Ticker,
+};
+use crate::{dexscreener, specificcommandfinder, tokenindex, Context, Error, DB};
+use chrono::Utc;
+use poise::serenity_prelude::{Attachment, ChannelId, CreateAttachment, GuildId};
+use poise::CreateReply;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Larger backups are refused, the configuration of a guild is far smaller.
+const MAXFILESIZE: u32 = 1024 * 1024;
+/// Snapshots that are larger are only written to disk, as Discord refuses large attachments.
+const MAXATTACHMENTSIZE: usize = 10 * 1024 * 1024;
+/// The version of the backup format, raised when a backup of an older version can no longer be read the same way.
+const BACKUPVERSION: u32 = 1;
+/// All tables that are written to a snapshot.
+const TABLES: [&str; 16] = [
+    "schemaversion",
+    "Coins",
+    "guildcommands",
+    "availablesettings",
+    "availableslashcommands",
+    "autodetect",
+    "ticker",
+    "statchannels",
+    "digests",
+    "boostfeeds",
+    "seenboosts",
+    "pairfeeds",
+    "seenpairs",
+    "marketalerts",
+    "tokenrequests",
+    "guildpermissions",
+];
+
+/// The configuration of a guild.
+/// Settings that are tied to channels can only be restored in the guild they were exported from.
+#[derive(Debug, Serialize, Deserialize)]
+struct GuildBackup {
+    version: u32,
+    guildid: GuildId,
+    exportedat: i64,
+    commands: Option<GuildCommands>,
+    availablesettings: Option<AvailableSettings>,
+    availableslashcommands: Option<AvailableSlashcommands>,
+    tokens: Vec<Coins>,
+    ticker: Option<Ticker>,
+    autodetect: Option<AutoDetect>,
+    statchannels: Vec<StatChannel>,
+    digests: Vec<Digest>,
+    boostfeeds: Vec<BoostFeed>,
+    pairfeeds: Vec<PairFeed>,
+    marketalerts: Option<MarketAlerts>,
+    /// Added after the first version of backups, older backups do not have it
+    #[serde(default)]
+    permissions: Option<GuildPermissions>,
+}
+
+/// Back up the configuration of this guild, or the whole database.
+#[poise::command(
+    slash_command,
+    subcommands("export", "restore", "snapshot"),
+    subcommand_required
+)]
+#[allow(clippy::unused_async)]
+pub async fn backup(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Export the commands, permissions, tokens and settings of this guild as a JSON file.
+#[poise::command(slash_command, check = "administratorcheck")]
+pub async fn export(ctx: Context<'_>) -> Result<(), Error> {
+    let guildid = getguildid(ctx).await?;
+    ctx.defer_ephemeral().await?;
+
+    let key = guildid.to_string();
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let statchannels: Vec<StatChannel> = DB.select("statchannels").await?;
+    let digests: Vec<Digest> = DB.select("digests").await?;
+    let boostfeeds: Vec<BoostFeed> = DB.select("boostfeeds").await?;
+    let pairfeeds: Vec<PairFeed> = DB.select("pairfeeds").await?;
+    let backup = GuildBackup {
+        version: BACKUPVERSION,
+        guildid,
+        exportedat: Utc::now().timestamp(),
+        commands: DB.select(("guildcommands", key.clone())).await?,
+        availablesettings: DB.select(("availablesettings", key.clone())).await?,
+        availableslashcommands: DB.select(("availableslashcommands", key.clone())).await?,
+        // Global tokens are available in every guild, so only the tokens of the guild itself are exported
+        tokens: coins
+            .into_iter()
+            .filter(|coin| coin.guildid == guildid && !coin.global)
+            .collect(),
+        ticker: DB.select(("ticker", key.clone())).await?,
+        autodetect: DB.select(("autodetect", key.clone())).await?,
+        statchannels: statchannels
+            .into_iter()
+            .filter(|x| x.guildid == guildid)
+            .collect(),
+        digests: digests
+            .into_iter()
+            .filter(|x| x.guildid == guildid)
+            .collect(),
+        boostfeeds: boostfeeds
+            .into_iter()
+            .filter(|x| x.guildid == guildid)
+            .collect(),
+        pairfeeds: pairfeeds
+            .into_iter()
+            .filter(|x| x.guildid == guildid)
+            .collect(),
+        marketalerts: DB.select(("marketalerts", key.clone())).await?,
+        permissions: DB.select(("guildpermissions", key)).await?,
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content("The configuration of this guild, `/backup restore` adds it to a guild again")
+            .attachment(CreateAttachment::bytes(
+                serde_json::to_string_pretty(&backup)?,
+                format!("backup-{guildid}.json"),
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Restore the configuration of a guild from a file made with `/backup export`.
+//
+// 1. Read the backup
+// 2. Restore the settings, channel settings only when the backup is of this guild and only for its channels
+// 3. Add the tokens that do not exist yet
+// 4. Register the restored commands and report what was restored
+#[allow(clippy::too_many_lines)]
+#[poise::command(slash_command, check = "administratorcheck")]
+pub async fn restore(
+    ctx: Context<'_>,
+    #[description = "JSON file made with /backup export"] file: Attachment,
+) -> Result<(), Error> {
+    let ownercheck = ownercheck(ctx, None).await?;
+    let guildid = getguildid(ctx).await?;
+    if file.size > MAXFILESIZE {
+        ctx.send(
+            CreateReply::default()
+                .content("This file is too large, the maximum is 1 MB")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+    ctx.defer_ephemeral().await?;
+
+    // 1. Read the backup
+    let backup: GuildBackup = match serde_json::from_slice(&file.download().await?) {
+        Ok(backup) => backup,
+        Err(parseerror) => {
+            ctx.say(format!("This is not a valid backup: {parseerror}"))
+                .await?;
+            return Ok(());
+        }
+    };
+    if backup.version > BACKUPVERSION {
+        ctx.say("This backup was made by a newer version of the bot")
+            .await?;
+        return Ok(());
+    }
+    let sameguild = backup.guildid == guildid;
+    let key = guildid.to_string();
+    let mut report: Vec<String> = vec![];
+
+    // 2. Restore the settings
+    let mut restoredcommands = None;
+    if let Some(commands) = backup.commands {
+        let mut allowed = commands.commands;
+        // The file can be edited, so guilds only get the commands the bot owner made available to them
+        if !ownercheck {
+            let available =
+                crate::settings::commandselection::getguildavailablecommands(guildid).await?;
+            let (kept, dropped): (Vec<String>, Vec<String>) = allowed
+                .into_iter()
+                .partition(|command| command == "settings" || available.contains(command));
+            if !dropped.is_empty() {
+                report.push(format!(
+                    "❌ Commands that are not available in this server: {}",
+                    dropped.join(", ")
+                ));
+            }
+            allowed = kept;
+        }
+        restoredcommands = Some(allowed.clone());
+        let _: Option<GuildCommands> = DB
+            .update(("guildcommands", key.clone()))
+            .content(GuildCommands {
+                guildid,
+                commands: allowed,
+            })
+            .await?;
+        report.push("✅ Registered commands".to_string());
+    }
+    // Permissions are given by the bot owner, so guilds can not restore them themselves
+    if backup.availablesettings.is_some() || backup.availableslashcommands.is_some() {
+        if ownercheck {
+            if let Some(availablesettings) = backup.availablesettings {
+                let _: Option<AvailableSettings> = DB
+                    .update(("availablesettings", key.clone()))
+                    .content(availablesettings)
+                    .await?;
+            }
+            if let Some(availableslashcommands) = backup.availableslashcommands {
+                let _: Option<AvailableSlashcommands> = DB
+                    .update(("availableslashcommands", key.clone()))
+                    .content(AvailableSlashcommands {
+                        guildid,
+                        ..availableslashcommands
+                    })
+                    .await?;
+            }
+            report.push("✅ Permissions and available commands".to_string());
+        } else {
+            report.push(
+                "❌ Permissions and available commands: only the bot owner can restore them"
+                    .to_string(),
+            );
+        }
+    }
+    if let Some(ticker) = backup.ticker {
+        let _: Option<Ticker> = DB
+            .update(("ticker", key.clone()))
+            .content(Ticker { guildid, ..ticker })
+            .await?;
+        report.push("✅ Ticker".to_string());
+    }
+
+    // Roles and members only exist in the guild the backup was made in
+    if let Some(permissions) = backup.permissions {
+        if sameguild {
+            let _: Option<GuildPermissions> = DB
+                .update(("guildpermissions", key.clone()))
+                .content(permissions)
+                .await?;
+            report.push("✅ Permissions of roles and members".to_string());
+        } else {
+            report.push(
+                "❌ Permissions of roles and members: these use roles of the guild the backup was made in"
+                    .to_string(),
+            );
+        }
+    }
+
+    let channelsettings = backup.autodetect.is_some()
+        || backup.marketalerts.is_some()
+        || !backup.statchannels.is_empty()
+        || !backup.digests.is_empty()
+        || !backup.boostfeeds.is_empty()
+        || !backup.pairfeeds.is_empty();
+    if channelsettings && !sameguild {
+        report.push("❌ Detection, stat channels, digests, feeds and market alerts: these use channels of the guild the backup was made in".to_string());
+    } else if channelsettings {
+        // The file can be edited, so every record gets this guild and only channels of this guild are kept
+        let channels = guildid.channels(ctx).await?;
+        let mut skipped: Vec<ChannelId> = vec![];
+        let mut ownchannel = |channelid: ChannelId| {
+            let own = channels.contains_key(&channelid);
+            if !own {
+                skipped.push(channelid);
+            }
+            own
+        };
+        if let Some(autodetect) = backup.autodetect {
+            let channels: Vec<ChannelId> = autodetect
+                .channels
+                .into_iter()
+                .filter(|channelid| ownchannel(*channelid))
+                .collect();
+            let _: Option<AutoDetect> = DB
+                .update(("autodetect", key.clone()))
+                .content(AutoDetect {
+                    guildid,
+                    channels,
+                    ..autodetect
+                })
+                .await?;
+        }
+        if let Some(marketalerts) = backup
+            .marketalerts
+            .filter(|marketalerts| ownchannel(marketalerts.channelid))
+        {
+            let _: Option<MarketAlerts> = DB
+                .update(("marketalerts", key.clone()))
+                .content(MarketAlerts {
+                    guildid,
+                    ..marketalerts
+                })
+                .await?;
+        }
+        for statchannel in backup.statchannels {
+            if ownchannel(statchannel.channelid) {
+                let _: Option<StatChannel> = DB
+                    .update(("statchannels", statchannel.channelid.to_string()))
+                    .content(StatChannel {
+                        guildid,
+                        ..statchannel
+                    })
+                    .await?;
+            }
+        }
+        for digest in backup.digests {
+            if ownchannel(digest.channelid) {
+                let _: Option<Digest> = DB
+                    .update(("digests", digest.channelid.to_string()))
+                    .content(Digest { guildid, ..digest })
+                    .await?;
+            }
+        }
+        for boostfeed in backup.boostfeeds {
+            if ownchannel(boostfeed.channelid) {
+                let _: Option<BoostFeed> = DB
+                    .update(("boostfeeds", boostfeed.channelid.to_string()))
+                    .content(BoostFeed {
+                        guildid,
+                        ..boostfeed
+                    })
+                    .await?;
+            }
+        }
+        for pairfeed in backup.pairfeeds {
+            if ownchannel(pairfeed.channelid) {
+                let _: Option<PairFeed> = DB
+                    .update(("pairfeeds", pairfeed.channelid.to_string()))
+                    .content(PairFeed {
+                        guildid,
+                        ..pairfeed
+                    })
+                    .await?;
+            }
+        }
+        report.push("✅ Detection, stat channels, digests, feeds and market alerts".to_string());
+        if !skipped.is_empty() {
+            let skipped: Vec<String> = skipped.iter().map(ToString::to_string).collect();
+            report.push(format!(
+                "❌ Channels that are not in this server: {}",
+                skipped.join(", ")
+            ));
+        }
+    }
+
+    // 3. Add the tokens, the file can be edited so tokens get the same checks as an import
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let pairs = if ownercheck {
+        vec![]
+    } else {
+        let addresses: Vec<String> = backup
+            .tokens
+            .iter()
+            .map(|token| token.address.clone())
+            .collect();
+        dexscreener::tokenspairs(&addresses).await?
+    };
+    let existing: BTreeMap<String, &Coins> = coins
+        .iter()
+        .map(|coin| (coin.name.to_lowercase(), coin))
+        .collect();
+    let mut newcoins: Vec<Coins> = vec![];
+    for token in backup.tokens {
+        match existing.get(&token.name.to_lowercase()) {
+            Some(coin) if coin.address.eq_ignore_ascii_case(&token.address) => {}
+            Some(_) => report.push(format!(
+                "❌ Token {}: there already is a token with this name",
+                token.name
+            )),
+            None if ownercheck => newcoins.push(token),
+            None => match checkfiletoken(
+                &coins,
+                &pairs,
+                token.chain.as_deref(),
+                &token.address,
+                Some(&token.name),
+                ownercheck,
+            ) {
+                Ok((_, pair)) => newcoins.push(Coins {
+                    address: pair.base_token.address.clone(),
+                    chain: Some(pair.chain_id.clone()),
+                    ..token
+                }),
+                Err(tokenerror) => {
+                    report.push(format!("❌ Token {}: {tokenerror}", token.name));
+                }
+            },
+        }
+    }
+    for token in newcoins {
+        report.push(format!("✅ Token {}", token.name));
+        let _: Option<Coins> = DB
+            .create(("Coins", token.name.clone()))
+            .content(Coins {
+                guildid,
+                global: false,
+                // Only the bot owner can verify tokens
+                verified: token.verified && ownercheck,
+                ..token
+            })
+            .await?;
+    }
+    tokenindex::tryrefresh().await;
+
+    // 4. Register the restored commands
+    if let Some(commands) = restoredcommands {
+        let commandregistery = specificcommandfinder(commands, &ctx.framework().options.commands);
+        if let Err(registererror) = guildid.set_commands(ctx, commandregistery).await {
+            report.push(format!(
+                "❌ Could not register the commands: {registererror}"
+            ));
+        }
+    }
+
+    ctx.say(if report.is_empty() {
+        "The backup is empty, nothing was restored".to_string()
+    } else {
+        format!("Restored the backup:\n{}", report.join("\n"))
+    })
+    .await?;
+    Ok(())
+}
+
+/// Write every table of the database to a JSON file, only for the bot owner.
+#[poise::command(slash_command, check = "botownercheck")]
+pub async fn snapshot(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut tables: BTreeMap<&str, Vec<serde_json::Value>> = BTreeMap::new();
+    for table in TABLES {
+        let records: Vec<serde_json::Value> = DB.select(table).await?;
+        tables.insert(table, records);
+    }
+    let snapshot = serde_json::to_string_pretty(&tables)?;
+
+    let directory = std::env::var("SNAPSHOT_DIR").unwrap_or("snapshots".to_string());
+    std::fs::create_dir_all(&directory)?;
+    let filename = format!("snapshot-{}.json", Utc::now().format("%Y%m%d-%H%M%S"));
+    let path = std::path::Path::new(&directory).join(&filename);
+    std::fs::write(&path, &snapshot)?;
+
+    let mut reply = CreateReply::default().ephemeral(true);
+    if snapshot.len() > MAXATTACHMENTSIZE {
+        reply = reply.content(format!(
+            "Saved the snapshot in {}, it is too large to attach",
+            path.display()
+        ));
+    } else {
+        reply = reply
+            .content(format!("Saved the snapshot in {}", path.display()))
+            .attachment(CreateAttachment::bytes(snapshot, filename));
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
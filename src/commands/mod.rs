@@ -1,4 +1,9 @@
 pub mod addtoken;
+pub mod backup;
 pub mod help;
+pub mod movers;
 pub mod price;
-pub mod pricewithoutdb;
+pub mod pricecard;
+pub mod pricemessage;
+pub mod tokens;
+pub mod trending;
@@ -0,0 +1,198 @@
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::dexscreener::{self, Pair};
+use crate::formatting::{self, Locale};
+use crate::settings::commonfunctions::{getguildid, guildcoins};
+use crate::{Context, Error};
+use poise::serenity_prelude::{
+    ButtonStyle, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use poise::{ChoiceParameter, CreateReply};
+
+/// The amount of tokens shown on one page.
+const PAGESIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum Timeframe {
+    #[name = "5m"]
+    M5,
+    #[name = "1h"]
+    H1,
+    #[name = "6h"]
+    H6,
+    #[name = "24h"]
+    H24,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Ranking {
+    #[name = "Top gainers"]
+    Gainers,
+    #[name = "Top losers"]
+    Losers,
+    #[name = "Most volume"]
+    Volume,
+}
+
+impl Timeframe {
+    fn pricechange(self, pair: &Pair) -> Option<f64> {
+        let pricechange = pair.price_change.as_ref()?;
+        match self {
+            Timeframe::M5 => pricechange.m5,
+            Timeframe::H1 => pricechange.h1,
+            Timeframe::H6 => pricechange.h6,
+            Timeframe::H24 => pricechange.h24,
+        }
+    }
+
+    fn volume(self, pair: &Pair) -> Option<f64> {
+        let volume = pair.volume.as_ref()?;
+        match self {
+            Timeframe::M5 => volume.m5,
+            Timeframe::H1 => volume.h1,
+            Timeframe::H6 => volume.h6,
+            Timeframe::H24 => volume.h24,
+        }
+    }
+}
+
+/// Show which tokens of this guild moved the most.
+#[allow(clippy::too_many_lines)]
+#[poise::command(slash_command)]
+pub async fn movers(
+    ctx: Context<'_>,
+    #[description = "Timeframe of the change, 24h if not chosen"] timeframe: Option<Timeframe>,
+    #[description = "How to rank the tokens, top gainers if not chosen"] ranking: Option<Ranking>,
+) -> Result<(), Error> {
+    let guildid = getguildid(ctx).await?;
+    let timeframe = timeframe.unwrap_or(Timeframe::H24);
+    let ranking = ranking.unwrap_or(Ranking::Gainers);
+    ctx.defer().await?;
+
+    let coins = guildcoins(guildid).await?;
+    let addresses: Vec<String> = coins.iter().map(|coin| coin.address.clone()).collect();
+    let pairs = dexscreener::bestpairs(&addresses).await?;
+    let mut coinpairs: Vec<(&Coins, &Pair)> = coins
+        .iter()
+        .filter_map(|coin| Some((coin, pairs.get(&coin.address.to_lowercase())?)))
+        .collect();
+    if coinpairs.is_empty() {
+        ctx.say("None of the tokens of this guild are available on Dexscreener")
+            .await?;
+        return Ok(());
+    }
+
+    let pricechange = |pair: &Pair| timeframe.pricechange(pair).unwrap_or(0.0);
+    let volume = |pair: &Pair| timeframe.volume(pair).unwrap_or(0.0);
+    match ranking {
+        Ranking::Gainers => {
+            coinpairs.sort_by(|a, b| pricechange(b.1).total_cmp(&pricechange(a.1)));
+        }
+        Ranking::Losers => {
+            coinpairs.sort_by(|a, b| pricechange(a.1).total_cmp(&pricechange(b.1)));
+        }
+        Ranking::Volume => coinpairs.sort_by(|a, b| volume(b.1).total_cmp(&volume(a.1))),
+    }
+
+    let locale = Locale::from_env();
+    let pages: Vec<String> = coinpairs
+        .chunks(PAGESIZE)
+        .enumerate()
+        .map(|(pageindex, chunk)| {
+            chunk
+                .iter()
+                .enumerate()
+                .map(|(index, (coin, pair))| {
+                    let price = match &pair.price_usd {
+                        Some(val) => formatting::pricestr(val, locale),
+                        None => "$N/A".to_string(),
+                    };
+                    format!(
+                        "**{}. {}** {price} *({})* · Vol {}",
+                        pageindex * PAGESIZE + index + 1,
+                        coin.name,
+                        formatting::percentage(pricechange(pair), locale),
+                        formatting::compactusd(volume(pair), locale)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect();
+    let title = format!("{} ({})", ranking.name(), timeframe.name());
+    let page = |index: usize| {
+        CreateEmbed::default()
+            .title(&title)
+            .description(&pages[index])
+            .footer(CreateEmbedFooter::new(format!(
+                "Page {}/{} · All rights reserved to Dexscreener.com",
+                index + 1,
+                pages.len()
+            )))
+    };
+
+    if pages.len() == 1 {
+        ctx.send(CreateReply::default().embed(page(0))).await?;
+        return Ok(());
+    }
+
+    let previousid = format!("moversprevious{}", ctx.id());
+    let nextid = format!("moversnext{}", ctx.id());
+    let buttons = |index: usize| {
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(previousid.clone())
+                .label("Previous")
+                .style(ButtonStyle::Secondary)
+                .disabled(index == 0),
+            CreateButton::new(nextid.clone())
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(index + 1 == pages.len()),
+        ])
+    };
+
+    let mut currentpage = 0;
+    let replyhandle = ctx
+        .send(
+            CreateReply::default()
+                .embed(page(currentpage))
+                .components(vec![buttons(currentpage)]),
+        )
+        .await?;
+    let replymessage = replyhandle.message().await?;
+
+    // Keep turning pages until nobody used the buttons for 5 minutes
+    while let Some(interaction) = replymessage
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .timeout(std::time::Duration::from_secs(60 * 5))
+        .custom_ids(vec![previousid.clone(), nextid.clone()])
+        .await
+    {
+        if interaction.data.custom_id == nextid {
+            currentpage = (currentpage + 1).min(pages.len() - 1);
+        } else {
+            currentpage = currentpage.saturating_sub(1);
+        }
+        interaction
+            .create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(page(currentpage))
+                        .components(vec![buttons(currentpage)]),
+                ),
+            )
+            .await?;
+    }
+
+    replyhandle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .embed(page(currentpage))
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
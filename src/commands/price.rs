@@ -1,65 +1,125 @@
 #![allow(clippy::struct_field_names)]
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::cast_possible_truncation)]
-#![cfg(feature = "database")]
-use crate::{Context, Data, Error, DB};
-use futures::{Stream, StreamExt};
+use crate::commands::pricecard::{pricebuttons, pricecard, withrisk, withverified};
+use crate::tokenstore::{store, TokenStore};
+use crate::{dexscreener, risk, tokenindex};
+use crate::{Data, Error};
 use poise::CreateReply;
 use poise::Modal;
+#[cfg(feature = "database")]
 use serde::{Deserialize, Serialize};
 use serenity::all::GuildId;
-use serenity::all::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter};
-use serenity::model::Colour;
 
+/// A token in the `Coins` table of the database.
+#[cfg(feature = "database")]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Coins {
     pub name: String,
     pub address: String,
     pub guildid: GuildId,
     pub global: bool,
+    /// Verified by the bot owner, tokens added before verification existed are not verified
+    #[serde(default)]
+    pub verified: bool,
+    /// The chain on Dexscreener, e.g. `ethereum`. Unknown for tokens that were no longer on Dexscreener when chains were added
+    #[serde(default)]
+    pub chain: Option<String>,
 }
 
 #[derive(Debug, Modal, Clone)]
 #[name = "Custom Token"]
 struct CustomToken {
     #[name = "Enter address of the token"] // Field name by default
-    #[placeholder = "0x....."] // No placeholder by default
-    #[min_length = 42] // No length restriction by default (so, 1-4000 chars)
-    #[max_length = 42]
+    #[placeholder = "0x..... or a Solana address"] // No placeholder by default
+    #[min_length = 32] // Solana addresses are 32 to 44 characters, EVM addresses 42
+    #[max_length = 44]
     address: String,
 }
 
-async fn autocomplete_name<'a>(
-    _ctx: Context<'_>,
-    partial: &'a str,
-) -> impl Stream<Item = String> + 'a {
-    let coins: Result<Vec<Coins>, surrealdb::Error> = DB.select("Coins").await;
-    let coins = match coins {
-        Ok(coins) => coins.iter().map(|x| x.name.clone()).collect(),
-        Err(_) => vec![],
-    };
+/// What the text entered in `/price` refers to.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resolved {
+    /// A token known to the bot
+    Known { address: String, verified: bool },
+    /// An address that is not known to the bot
+    Custom(String),
+    /// Neither a known token nor an address
+    Unknown,
+}
 
-    futures::stream::iter(coins)
-        .filter(move |name| futures::future::ready(name.starts_with(partial)))
-        .map(|name| name.to_string())
+/// Find what was entered in `/price`.
+/// Autocomplete submits the address, but a typed alias or symbol works as well.
+/// Tokens added since the last refresh of the index are still found in the store.
+pub async fn resolve(store: &impl TokenStore, coin: &str, guildid: Option<GuildId>) -> Resolved {
+    let coin = coin.trim();
+    if let Some(token) = store.find(coin, guildid) {
+        return Resolved::Known {
+            address: token.address,
+            verified: token.verified,
+        };
+    }
+    // An unavailable database should not stop prices of addresses
+    match store.lookup(coin, guildid).await {
+        Ok(Some(token)) => {
+            return Resolved::Known {
+                address: token.address,
+                verified: token.verified,
+            }
+        }
+        Ok(None) => {}
+        Err(storeerror) => println!("Could not look up {coin}: {storeerror}"),
+    }
+    if dexscreener::isevmaddress(coin) || dexscreener::issolanaaddress(coin) {
+        Resolved::Custom(coin.to_string())
+    } else {
+        Resolved::Unknown
+    }
 }
 
-/// Find the price of any coin in the Bots database. If not available allow for custom address search.
+/// Find the price of any coin known to the bot. If not available allow for custom address search.
 #[poise::command(slash_command)]
 pub async fn price(
     ctx: poise::ApplicationContext<'_, Data, Error>,
-    #[autocomplete = "autocomplete_name"]
+    #[autocomplete = "tokenindex::autocomplete"]
     #[description = "Coin to find price from"]
     coin: String,
 ) -> Result<(), Error> {
-    let optionspecificcoin: Option<Coins> = DB.select(("Coins", coin.clone())).await?;
+    let guildid = ctx.guild_id();
 
-    let address = match optionspecificcoin {
-        Some(coin) => coin.address,
-        None => {
+    // Tokens that are entered by hand are unknown, so they get a risk assessment
+    let mut custom = false;
+    let mut verified = false;
+    let address = match resolve(&store(), &coin, guildid).await {
+        Resolved::Known {
+            address,
+            verified: isverified,
+        } => {
+            verified = isverified;
+            address
+        }
+        Resolved::Custom(address) => {
+            custom = true;
+            address
+        }
+        Resolved::Unknown => {
             let data = CustomToken::execute(ctx).await?;
             match data {
-                Some(val) => val.address,
+                Some(val)
+                    if dexscreener::isevmaddress(val.address.trim())
+                        || dexscreener::issolanaaddress(val.address.trim()) =>
+                {
+                    custom = true;
+                    val.address.trim().to_string()
+                }
+                Some(val) => {
+                    ctx.say(format!(
+                        "`{}` is not an EVM or Solana address",
+                        val.address.trim()
+                    ))
+                    .await?;
+                    return Ok(());
+                }
                 None => {
                     ctx.say("This token is not available on dexscreener")
                         .await?;
@@ -69,86 +129,133 @@ pub async fn price(
         }
     };
     ctx.defer().await?;
-    let url = format!("https://api.dexscreener.com/latest/dex/tokens/{address}");
-    let client = reqwest::Client::new();
-    let result = client.get(url).send().await?;
-    let mut parsedresult = result.json::<Root>().await?;
-    parsedresult.pairs.sort_by_key(|x| {
-        x.volume
-            .clone()
-            .unwrap_or_default()
-            .h24
-            .unwrap_or(0.0)
-            .round() as i64
-    });
-    let pair = match parsedresult.pairs.last() {
-        Some(val) => val,
-        None => {
-            ctx.say(format!("{} is not available on Dexscreener", coin.clone()))
-                .await?;
-            return Ok(());
-        }
-    };
-    let price = match &pair.price_usd {
-        Some(val) => val,
-        None => return Ok(()),
-    };
-    let pricechange = match &pair.price_change {
-        Some(change) => change.h24.unwrap_or(0.0),
-        None => return Ok(()),
-    };
-    let colour = if pricechange >= 0.0 {
-        Colour::from_rgb(0, 255, 0)
-    } else {
-        Colour::from_rgb(255, 0, 0)
+    let Some(pair) = dexscreener::bestpair(&address).await? else {
+        ctx.say(format!("{} is not available on Dexscreener", coin.clone()))
+            .await?;
+        return Ok(());
     };
-    let nametoken = &pair.base_token.name;
-
-    let embed = CreateEmbed::default()
-        .author(CreateEmbedAuthor::new(nametoken))
-        .title(format!("${price}    *( {pricechange}%)*"))
-        .footer(CreateEmbedFooter::new(
-            "All rights reserved to Dexscreener.com",
-        ))
-        .colour(colour);
-
-    ctx.send(CreateReply::default().embed(embed)).await?;
+    if pair.price_usd.is_none() {
+        return Ok(());
+    }
+    // Only tokens in the index are ranked, custom addresses would only fill the usage counts
+    if !custom {
+        tokenindex::recordusage(guildid, &address);
+    }
+    let mut embed = pricecard(&pair);
+    if custom {
+        embed = withrisk(embed, &risk::assess(&pair, &risk::knowntokens().await));
+    }
+    if verified {
+        embed = withverified(embed);
+    }
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .components(vec![pricebuttons(&pair)]),
+    )
+    .await?;
     Ok(())
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Root {
-    pub schema_version: String,
-    pub pairs: Vec<Pair>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenstore::{MemoryStore, StoredToken};
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Pair {
-    pub url: String,
-    pub base_token: BaseToken,
-    pub price_usd: Option<String>,
-    pub volume: Option<Volume>,
-    pub price_change: Option<PriceChange>,
-}
+    const OPENX: &str = "0xc3864f98f2a61a7caeb95b039d031b4e2f55e0e9";
+    const OP: &str = "0x4200000000000000000000000000000000000042";
+    const WETH: &str = "0x4200000000000000000000000000000000000006";
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BaseToken {
-    pub address: String,
-    pub name: String,
-    pub symbol: String,
-}
+    fn teststore() -> MemoryStore {
+        MemoryStore::new(vec![
+            StoredToken {
+                name: "OPENX".to_string(),
+                address: OPENX.to_string(),
+                guildid: Some(GuildId::new(1)),
+                global: false,
+                verified: false,
+                chain: Some("optimism".to_string()),
+            },
+            // Added under an alias that differs from the symbol on Dexscreener
+            StoredToken {
+                name: "Optimism".to_string(),
+                address: OP.to_string(),
+                guildid: Some(GuildId::new(1)),
+                global: true,
+                verified: true,
+                chain: Some("optimism".to_string()),
+            },
+            StoredToken {
+                name: "WETH".to_string(),
+                address: WETH.to_string(),
+                guildid: Some(GuildId::new(2)),
+                global: false,
+                verified: false,
+                chain: None,
+            },
+        ])
+    }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Volume {
-    pub h24: Option<f64>,
-}
+    fn known(address: &str, verified: bool) -> Resolved {
+        Resolved::Known {
+            address: address.to_string(),
+            verified,
+        }
+    }
+
+    #[tokio::test]
+    async fn exactmatches() {
+        let store = teststore();
+        let guild = Some(GuildId::new(1));
+        assert_eq!(resolve(&store, "OPENX", guild).await, known(OPENX, false));
+        assert_eq!(resolve(&store, " openx ", guild).await, known(OPENX, false));
+        assert_eq!(
+            resolve(&store, &OPENX.to_uppercase(), guild).await,
+            known(OPENX, false)
+        );
+    }
+
+    #[tokio::test]
+    async fn aliases() {
+        let store = teststore();
+        assert_eq!(
+            resolve(&store, "optimism", Some(GuildId::new(1))).await,
+            known(OP, true)
+        );
+    }
+
+    #[tokio::test]
+    async fn guildscoped() {
+        let store = teststore();
+        assert_eq!(
+            resolve(&store, "WETH", Some(GuildId::new(2))).await,
+            known(WETH, false)
+        );
+        assert_eq!(
+            resolve(&store, "WETH", Some(GuildId::new(1))).await,
+            Resolved::Unknown
+        );
+        // The address still gives a price in other guilds, but as an unknown token
+        assert_eq!(
+            resolve(&store, WETH, Some(GuildId::new(1))).await,
+            Resolved::Custom(WETH.to_string())
+        );
+        assert_eq!(resolve(&store, "OPENX", None).await, Resolved::Unknown);
+    }
+
+    #[tokio::test]
+    async fn global() {
+        let store = teststore();
+        for guildid in [None, Some(GuildId::new(1)), Some(GuildId::new(3))] {
+            assert_eq!(resolve(&store, "Optimism", guildid).await, known(OP, true));
+            assert_eq!(resolve(&store, OP, guildid).await, known(OP, true));
+        }
+    }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PriceChange {
-    pub h24: Option<f64>,
+    #[tokio::test]
+    async fn unknowntext() {
+        let store = teststore();
+        assert_eq!(resolve(&store, "BTC", None).await, Resolved::Unknown);
+        assert_eq!(resolve(&store, "", None).await, Resolved::Unknown);
+    }
 }
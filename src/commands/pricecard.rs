@@ -0,0 +1,251 @@
+use crate::dexscreener::Pair;
+use crate::formatting::{self, Locale};
+use crate::risk::Risk;
+use serenity::all::{
+    ButtonStyle, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
+};
+use serenity::model::Colour;
+
+/// The buttons on a price card that are handled by the bot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceButton {
+    Refresh,
+    Details,
+}
+
+impl PriceButton {
+    fn name(self) -> &'static str {
+        match self {
+            PriceButton::Refresh => "refresh",
+            PriceButton::Details => "details",
+        }
+    }
+}
+
+/// Create the embed that shows the price of a pair.
+/// Green if the price went up in the last 24 hours, red if it went down.
+pub fn pricecard(pair: &Pair) -> CreateEmbed {
+    let locale = Locale::from_env();
+    let price = match &pair.price_usd {
+        Some(val) => formatting::pricestr(val, locale),
+        None => "$N/A".to_string(),
+    };
+    let pricechange = match &pair.price_change {
+        Some(change) => change.h24.unwrap_or(0.0),
+        None => 0.0,
+    };
+    let colour = if pricechange >= 0.0 {
+        Colour::from_rgb(0, 255, 0)
+    } else {
+        Colour::from_rgb(255, 0, 0)
+    };
+    let nametoken = &pair.base_token.name;
+
+    let mut embed = CreateEmbed::default()
+        .author(CreateEmbedAuthor::new(nametoken))
+        .title(format!(
+            "{price}    *({})*",
+            formatting::percentage(pricechange, locale)
+        ))
+        .footer(CreateEmbedFooter::new(
+            "All rights reserved to Dexscreener.com",
+        ))
+        .colour(colour);
+    if let Some(volume) = pair.volume.as_ref().and_then(|x| x.h24) {
+        embed = embed.field("Volume 24h", formatting::compactusd(volume, locale), true);
+    }
+    embed
+}
+
+/// Add the risk badge and the warning signs that were found to a price card.
+pub fn withrisk(embed: CreateEmbed, risk: &Risk) -> CreateEmbed {
+    let explanation = if risk.reasons.is_empty() {
+        "No warning signs found".to_string()
+    } else {
+        risk.reasons
+            .iter()
+            .map(|reason| format!("⚠️ {reason}"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    embed.field(format!("Risk: {}", risk.badge()), explanation, false)
+}
+
+/// The name of the field that marks a token as verified, also used to find it again when refreshing.
+pub const VERIFIEDFIELD: &str = "✅ Verified token";
+
+/// Mark a price card as a token that is verified by the bot owner.
+pub fn withverified(embed: CreateEmbed) -> CreateEmbed {
+    embed.field(
+        VERIFIEDFIELD,
+        "This token is verified by the bot owner",
+        false,
+    )
+}
+
+/// Create a small embed with only the symbol, price and change, used when replying to messages automatically.
+pub fn compactcard(pair: &Pair) -> CreateEmbed {
+    let locale = Locale::from_env();
+    let price = match &pair.price_usd {
+        Some(val) => formatting::pricestr(val, locale),
+        None => "$N/A".to_string(),
+    };
+    let pricechange = match &pair.price_change {
+        Some(change) => change.h24.unwrap_or(0.0),
+        None => 0.0,
+    };
+    let colour = if pricechange >= 0.0 {
+        Colour::from_rgb(0, 255, 0)
+    } else {
+        Colour::from_rgb(255, 0, 0)
+    };
+
+    CreateEmbed::default()
+        .title(format!(
+            "{} ({})    {price}    *({})*",
+            pair.base_token.symbol,
+            pair.chain_id,
+            formatting::percentage(pricechange, locale)
+        ))
+        .url(pair.url.clone())
+        .colour(colour)
+}
+
+/// The footer of the cards in new pair feeds, also used to find them again when refreshing.
+pub const NEWPAIRFOOTER: &str = "New pair on Dexscreener";
+
+/// Create the compact card posted in a new pair feed, with the DEX, liquidity and age of the pair.
+pub fn newpaircard(pair: &Pair) -> CreateEmbed {
+    let locale = Locale::from_env();
+    let liquidity = match pair.liquidity.as_ref().and_then(|x| x.usd) {
+        Some(val) => formatting::compactusd(val, locale),
+        None => "N/A".to_string(),
+    };
+    let quote = match &pair.quote_token {
+        Some(token) => format!("{}/{}", pair.base_token.symbol, token.symbol),
+        None => pair.base_token.symbol.clone(),
+    };
+    let mut parts = vec![
+        format!("🆕 {quote} on {}", pair.dex_id),
+        format!("Liquidity {liquidity}"),
+    ];
+    if let Some(createdat) = pair.pair_created_at {
+        parts.push(format!("Created <t:{}:R>", createdat / 1000));
+    }
+    compactcard(pair)
+        .description(format!(
+            "{}\n`{}`",
+            parts.join(" · "),
+            pair.base_token.address
+        ))
+        .footer(CreateEmbedFooter::new(NEWPAIRFOOTER))
+}
+
+/// Create the embed with all information about a pair, shown when clicking "More details".
+pub fn detailcard(pair: &Pair) -> CreateEmbed {
+    let locale = Locale::from_env();
+    let usd = |value: Option<f64>| match value {
+        Some(val) => formatting::compactusd(val, locale),
+        None => "N/A".to_string(),
+    };
+    let change = |value: Option<f64>| match value {
+        Some(val) => formatting::percentage(val, locale),
+        None => "N/A".to_string(),
+    };
+    let volume = pair.volume.clone().unwrap_or_default();
+    let pricechange = pair.price_change.clone().unwrap_or_default();
+    let txns = pair
+        .txns
+        .clone()
+        .unwrap_or_default()
+        .h24
+        .unwrap_or_default();
+    let quotesymbol = match &pair.quote_token {
+        Some(quotetoken) => quotetoken.symbol.clone(),
+        None => "?".to_string(),
+    };
+
+    let mut embed = pricecard(pair)
+        .description(format!(
+            "**{}/{quotesymbol}** on {} ({})\n`{}`",
+            pair.base_token.symbol, pair.dex_id, pair.chain_id, pair.base_token.address
+        ))
+        .field("Market cap", usd(pair.market_cap), true)
+        .field("FDV", usd(pair.fdv), true)
+        .field(
+            "Liquidity",
+            usd(pair.liquidity.as_ref().and_then(|x| x.usd)),
+            true,
+        )
+        .field(
+            "Volume 5m / 1h / 6h",
+            format!(
+                "{} / {} / {}",
+                usd(volume.m5),
+                usd(volume.h1),
+                usd(volume.h6)
+            ),
+            false,
+        )
+        .field(
+            "Change 5m / 1h / 6h",
+            format!(
+                "{} / {} / {}",
+                change(pricechange.m5),
+                change(pricechange.h1),
+                change(pricechange.h6)
+            ),
+            false,
+        )
+        .field(
+            "Transactions 24h",
+            format!("{} buys / {} sells", txns.buys, txns.sells),
+            true,
+        );
+    if let Some(createdat) = pair.pair_created_at {
+        embed = embed.field("Pair created", format!("<t:{}:R>", createdat / 1000), true);
+    }
+    embed
+}
+
+/// Create the buttons shown below a price card.
+/// The custom ids contain the action, chain and pair address (`price:<action>:<chain>:<pairaddress>`),
+/// so they can be handled without keeping any state and keep working after the bot restarts.
+pub fn pricebuttons(pair: &Pair) -> CreateActionRow {
+    let buttonid = |button: PriceButton| {
+        format!(
+            "price:{}:{}:{}",
+            button.name(),
+            pair.chain_id,
+            pair.pair_address
+        )
+    };
+    let mut buttons = vec![
+        CreateButton::new(buttonid(PriceButton::Refresh))
+            .label("Refresh")
+            .style(ButtonStyle::Primary),
+        CreateButton::new(buttonid(PriceButton::Details))
+            .label("More details")
+            .style(ButtonStyle::Secondary),
+    ];
+    if pair.url.starts_with("https://") {
+        buttons.push(CreateButton::new_link(pair.url.clone()).label("Open on Dexscreener"));
+    }
+    CreateActionRow::Buttons(buttons)
+}
+
+/// Read the action, chain and pair address from the custom id of a price card button.
+pub fn parsebuttonid(customid: &str) -> Option<(PriceButton, &str, &str)> {
+    let mut parts = customid.strip_prefix("price:")?.splitn(3, ':');
+    let button = match parts.next()? {
+        "refresh" => PriceButton::Refresh,
+        "details" => PriceButton::Details,
+        _ => return None,
+    };
+    let chainid = parts.next()?;
+    let pairaddress = parts.next()?;
+    if chainid.is_empty() || pairaddress.is_empty() {
+        return None;
+    }
+    Some((button, chainid, pairaddress))
+}
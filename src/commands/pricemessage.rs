@@ -0,0 +1,133 @@
+use crate::commands::pricecard::{pricebuttons, pricecard, withrisk};
+use crate::dexscreener::{self, Pair};
+use crate::risk;
+use crate::{Context, Error};
+use poise::serenity_prelude::{
+    ComponentInteractionDataKind, CreateActionRow, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, Message,
+};
+use poise::CreateReply;
+
+/// Discord allows at most 25 options in a select menu.
+const MAXADDRESSES: usize = 25;
+
+/// Find the price of every contract address in a message.
+//
+// 1. Find all EVM and Solana addresses in the message
+// 2. Fetch the pair with the most volume for each address, in one batch
+// 3. Add the risk assessment, as addresses posted in chat are often unknown tokens
+// 4. Show the first price card, with a select menu to switch between them if there are several
+#[allow(clippy::too_many_lines)]
+#[poise::command(context_menu_command = "Price contract addresses")]
+pub async fn pricemessage(
+    ctx: Context<'_>,
+    #[description = "Message to search for contract addresses"] message: Message,
+) -> Result<(), Error> {
+    let addresses = dexscreener::findaddresses(&message.content);
+    if addresses.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .content("There are no contract addresses in this message")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+    ctx.defer().await?;
+
+    // One request per 30 addresses, the cards keep the order of the addresses in the message
+    let addresses: Vec<String> = addresses.into_iter().take(MAXADDRESSES).collect();
+    let mut bestpairs = dexscreener::bestpairs(&addresses).await?;
+    let pairs: Vec<Pair> = addresses
+        .iter()
+        .filter_map(|address| bestpairs.remove(&address.to_lowercase()))
+        .collect();
+    if pairs.is_empty() {
+        ctx.say("None of the addresses in this message are available on Dexscreener")
+            .await?;
+        return Ok(());
+    }
+    let knowntokens = risk::knowntokens().await;
+    let cards: Vec<CreateEmbed> = pairs
+        .iter()
+        .map(|pair| withrisk(pricecard(pair), &risk::assess(pair, &knowntokens)))
+        .collect();
+    if pairs.len() == 1 {
+        ctx.send(
+            CreateReply::default()
+                .embed(cards[0].clone())
+                .components(vec![pricebuttons(&pairs[0])]),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let customid = format!("pricemessagemenu{}", ctx.id());
+    let selectmenu = |selected: usize| {
+        let options = pairs
+            .iter()
+            .enumerate()
+            .map(|(index, pair)| {
+                CreateSelectMenuOption::new(
+                    format!("{} ({})", pair.base_token.symbol, pair.chain_id),
+                    index.to_string(),
+                )
+                .description(pair.base_token.address.clone())
+                .default_selection(index == selected)
+            })
+            .collect();
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(customid.clone(), CreateSelectMenuKind::String { options })
+                .placeholder("Choose a token"),
+        )
+    };
+
+    let replyhandle = ctx
+        .send(
+            CreateReply::default()
+                .embed(cards[0].clone())
+                .components(vec![selectmenu(0), pricebuttons(&pairs[0])]),
+        )
+        .await?;
+    let replymessage = replyhandle.message().await?;
+    let mut selected = 0;
+
+    // Keep switching between price cards until nobody used the menu for 5 minutes
+    while let Some(interaction) = replymessage
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .timeout(std::time::Duration::from_secs(60 * 5))
+        .custom_ids(vec![customid.clone()])
+        .await
+    {
+        let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind else {
+            continue;
+        };
+        selected = match values.first().and_then(|x| x.parse::<usize>().ok()) {
+            Some(index) if index < pairs.len() => index,
+            _ => continue,
+        };
+        interaction
+            .create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(cards[selected].clone())
+                        .components(vec![selectmenu(selected), pricebuttons(&pairs[selected])]),
+                ),
+            )
+            .await?;
+    }
+
+    // Editing replaces the embeds as well, so send the last shown price card again.
+    // The buttons stay, they are handled by the event handler instead of this collector.
+    replyhandle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .embed(cards[selected].clone())
+                .components(vec![pricebuttons(&pairs[selected])]),
+        )
+        .await?;
+    Ok(())
+}
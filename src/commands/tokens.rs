@@ -0,0 +1,653 @@
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::dexscreener::{self, Pair};
+use crate::settings::commonfunctions::{findlookalike, getguildid, managetokenscheck, ownercheck};
+use crate::{tokenindex, Context, Error, DB};
+use chrono::Utc;
+use poise::serenity_prelude::{Attachment, CreateAttachment};
+use poise::CreateReply;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Larger files are refused, a list of a few hundred tokens is far smaller.
+const MAXFILESIZE: u32 = 1024 * 1024;
+/// The maximum amount of tokens imported at once.
+const MAXROWS: usize = 500;
+/// EVM chain ids used in token lists, with the names Dexscreener uses for them.
+const CHAINIDS: [(u64, &str); 12] = [
+    (1, "ethereum"),
+    (10, "optimism"),
+    (56, "bsc"),
+    (100, "gnosischain"),
+    (137, "polygon"),
+    (250, "fantom"),
+    (324, "zksync"),
+    (8453, "base"),
+    (42161, "arbitrum"),
+    (43114, "avalanche"),
+    (59144, "linea"),
+    (81457, "blast"),
+];
+
+/// A token list as used by Uniswap, see <https://tokenlists.org>.
+/// Chains without an EVM chain id, like Solana, are written with chain id 0 and the chain in `extensions`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenList {
+    name: String,
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    version: TokenListVersion,
+    tokens: Vec<ListToken>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenListVersion {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListToken {
+    chain_id: u64,
+    address: String,
+    symbol: String,
+    #[serde(default)]
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decimals: Option<u8>,
+    #[serde(rename = "logoURI", skip_serializing_if = "Option::is_none")]
+    logo_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extensions: Option<ListTokenExtensions>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ListTokenExtensions {
+    chain: Option<String>,
+}
+
+/// A token read from an imported file, `row` is the line in a CSV or the position in a token list.
+#[derive(Debug)]
+struct ImportRow {
+    row: usize,
+    chain: String,
+    address: String,
+    alias: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ExportFormat {
+    #[name = "CSV (chain,address,alias)"]
+    Csv,
+    #[name = "Token list JSON"]
+    TokenList,
+}
+
+/// Import or export the tokens of this guild.
+#[poise::command(slash_command, subcommands("import", "export"), subcommand_required)]
+#[allow(clippy::unused_async)]
+pub async fn tokens(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Import tokens from a CSV file (chain,address,alias) or a token list JSON file.
+//
+// 1. Read the rows of the file
+// 2. Check every token on Dexscreener, and check for duplicates and lookalikes
+// 3. Add all valid tokens in one transaction and report the result of every row
+#[allow(clippy::too_many_lines)]
+#[poise::command(slash_command, check = "managetokenscheck")]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "CSV file with chain,address,alias rows or a token list JSON file"]
+    file: Attachment,
+) -> Result<(), Error> {
+    let ownercheck = ownercheck(ctx, None).await?;
+    let guildid = getguildid(ctx).await?;
+    if file.size > MAXFILESIZE {
+        ctx.send(
+            CreateReply::default()
+                .content("This file is too large, the maximum is 1 MB")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+    ctx.defer_ephemeral().await?;
+
+    // 1. Read the rows
+    let content = String::from_utf8(file.download().await?)?;
+    let rows = match readrows(&file.filename, &content) {
+        Ok(rows) => rows,
+        Err(parseerror) => {
+            ctx.say(format!("This is not a valid token list: {parseerror}"))
+                .await?;
+            return Ok(());
+        }
+    };
+    if rows.len() > MAXROWS {
+        ctx.say(format!(
+            "This file has {} tokens, at most {MAXROWS} can be imported at once",
+            rows.len()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    // 2. Check the tokens
+    let mut addresses: Vec<String> = vec![];
+    for row in rows.iter().flatten() {
+        if !addresses.contains(&row.address) {
+            addresses.push(row.address.clone());
+        }
+    }
+    let pairs = dexscreener::tokenspairs(&addresses).await?;
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let mut names: HashSet<String> = coins.iter().map(|coin| coin.name.to_lowercase()).collect();
+    let mut guildaddresses: HashSet<String> = coins
+        .iter()
+        .filter(|coin| coin.guildid == guildid)
+        .map(|coin| coin.address.to_lowercase())
+        .collect();
+
+    let mut report: Vec<String> = vec![];
+    let mut newcoins: Vec<Coins> = vec![];
+    for row in rows {
+        let row = match row {
+            Ok(row) => row,
+            Err(rowerror) => {
+                report.push(format!("❌ {rowerror}"));
+                continue;
+            }
+        };
+        let (name, pair) = match checkfiletoken(
+            &coins,
+            &pairs,
+            Some(&row.chain),
+            &row.address,
+            row.alias.as_deref(),
+            ownercheck,
+        ) {
+            Ok(checked) => checked,
+            Err(rowerror) => {
+                report.push(format!("❌ Row {}: {rowerror}", row.row));
+                continue;
+            }
+        };
+        if guildaddresses.contains(&pair.base_token.address.to_lowercase()) {
+            report.push(format!(
+                "❌ Row {}: {} already is a token of this server",
+                row.row, pair.base_token.address
+            ));
+            continue;
+        }
+        if !names.insert(name.to_lowercase()) {
+            report.push(format!(
+                "❌ Row {}: there already is a token called {name}",
+                row.row
+            ));
+            continue;
+        }
+        guildaddresses.insert(pair.base_token.address.to_lowercase());
+        report.push(format!("✅ Row {}: {name} ({})", row.row, row.chain));
+        newcoins.push(Coins {
+            name,
+            address: pair.base_token.address.clone(),
+            guildid,
+            global: false,
+            verified: false,
+            chain: Some(pair.chain_id.clone()),
+        });
+    }
+
+    // 3. Add the tokens, either all of them are added or none
+    let added = newcoins.len();
+    if !newcoins.is_empty() {
+        let statements: Vec<String> = (0..newcoins.len())
+            .map(|index| format!("CREATE type::thing('Coins', $name{index}) CONTENT $coin{index};"))
+            .collect();
+        let mut query = DB.query(format!(
+            "BEGIN TRANSACTION; {} COMMIT TRANSACTION;",
+            statements.join(" ")
+        ));
+        for (index, coin) in newcoins.into_iter().enumerate() {
+            query = query
+                .bind((format!("name{index}"), coin.name.clone()))
+                .bind((format!("coin{index}"), coin));
+        }
+        if let Err(importerror) = query.await.and_then(surrealdb::Response::check) {
+            ctx.say(format!(
+                "None of the tokens were imported, the database refused them: {importerror}"
+            ))
+            .await?;
+            return Ok(());
+        }
+        tokenindex::tryrefresh().await;
+    }
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "Imported {added} of {} tokens, the result of every row is in the attached file",
+                report.len()
+            ))
+            .attachment(CreateAttachment::bytes(report.join("\n"), "import.txt")),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Export the tokens of this guild as a CSV or token list JSON file, which can be imported again.
+#[poise::command(slash_command, check = "managetokenscheck")]
+pub async fn export(
+    ctx: Context<'_>,
+    #[description = "Format of the file, CSV if not chosen"] format: Option<ExportFormat>,
+) -> Result<(), Error> {
+    let guildid = getguildid(ctx).await?;
+    ctx.defer_ephemeral().await?;
+
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let coins: Vec<Coins> = coins
+        .into_iter()
+        .filter(|coin| coin.guildid == guildid && !coin.global)
+        .collect();
+    if coins.is_empty() {
+        ctx.say("This guild has no tokens of its own to export")
+            .await?;
+        return Ok(());
+    }
+    // Look up the chain on Dexscreener for tokens that are stored without one
+    let addresses: Vec<String> = coins
+        .iter()
+        .filter(|coin| coin.chain.is_none())
+        .map(|coin| coin.address.clone())
+        .collect();
+    let pairs = dexscreener::bestpairs(&addresses).await?;
+    // Tokens without a known chain can not be imported again, so they are left out and mentioned instead
+    let mut unknownchains: Vec<String> = vec![];
+    let coins: Vec<(String, Coins)> = coins
+        .into_iter()
+        .filter_map(
+            |coin| match (&coin.chain, pairs.get(&coin.address.to_lowercase())) {
+                (Some(chain), _) => Some((chain.clone(), coin)),
+                (None, Some(pair)) => Some((pair.chain_id.clone(), coin)),
+                (None, None) => {
+                    unknownchains.push(coin.name);
+                    None
+                }
+            },
+        )
+        .collect();
+    if coins.is_empty() {
+        ctx.say(format!(
+            "The chain of none of the tokens of this guild is known, so there is nothing to export: {}",
+            unknownchains.join(", ")
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let attachment = match format.unwrap_or(ExportFormat::Csv) {
+        ExportFormat::Csv => {
+            let mut lines = vec!["chain,address,alias".to_string()];
+            lines.extend(
+                coins
+                    .iter()
+                    .map(|(chain, coin)| csvline(chain, &coin.address, &coin.name)),
+            );
+            CreateAttachment::bytes(lines.join("\n"), "tokens.csv")
+        }
+        ExportFormat::TokenList => {
+            let tokenlist = TokenList {
+                name: "Dexscreener pricebot tokens".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+                version: TokenListVersion {
+                    major: 1,
+                    minor: 0,
+                    patch: 0,
+                },
+                tokens: coins
+                    .iter()
+                    .map(|(chain, coin)| {
+                        let chain = chain.clone();
+                        ListToken {
+                            chain_id: CHAINIDS
+                                .iter()
+                                .find(|(_, name)| *name == chain)
+                                .map_or(0, |(chainid, _)| *chainid),
+                            address: coin.address.clone(),
+                            symbol: coin.name.clone(),
+                            name: pairs
+                                .get(&coin.address.to_lowercase())
+                                .map_or(coin.name.clone(), |pair| pair.base_token.name.clone()),
+                            decimals: None,
+                            logo_uri: None,
+                            extensions: Some(ListTokenExtensions { chain: Some(chain) }),
+                        }
+                    })
+                    .collect(),
+            };
+            CreateAttachment::bytes(serde_json::to_vec_pretty(&tokenlist)?, "tokens.json")
+        }
+    };
+    let mut content = format!("Exported {} tokens", coins.len());
+    if !unknownchains.is_empty() {
+        content = format!(
+            "{content}\nLeft out because their chain is not known: {}",
+            unknownchains.join(", ")
+        );
+    }
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .attachment(attachment),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Read the rows of a token list JSON file or of a CSV file.
+fn readrows(filename: &str, content: &str) -> Result<Vec<Result<ImportRow, String>>, Error> {
+    // Spreadsheet programs often start the file with a byte order mark
+    let content = content.trim_start_matches('\u{feff}');
+    if filename.to_lowercase().ends_with(".json") || content.trim_start().starts_with('{') {
+        parsetokenlist(content)
+    } else {
+        Ok(parsecsv(content))
+    }
+}
+
+/// Read `chain,address,alias` rows. A header row and empty lines are skipped, the alias is optional.
+/// Fields can be quoted, so an alias can contain commas.
+fn parsecsv(content: &str) -> Vec<Result<ImportRow, String>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, csvfields(line)))
+        // Skip empty lines and the header row
+        .filter(|(_, columns)| {
+            columns.iter().any(|column| !column.is_empty())
+                && columns
+                    .first()
+                    .is_some_and(|first| !first.eq_ignore_ascii_case("chain"))
+        })
+        .map(|(row, columns)| {
+            let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+            match columns.as_slice() {
+                [chain, address] | [chain, address, ""] => Ok(ImportRow {
+                    row,
+                    chain: chain.to_lowercase(),
+                    address: (*address).to_string(),
+                    alias: None,
+                }),
+                [chain, address, alias] => Ok(ImportRow {
+                    row,
+                    chain: chain.to_lowercase(),
+                    address: (*address).to_string(),
+                    alias: Some((*alias).to_string()),
+                }),
+                _ => Err(format!("Row {row}: expected chain,address,alias")),
+            }
+        })
+        .map(|row| match row {
+            Ok(row)
+                if !dexscreener::isevmaddress(&row.address)
+                    && !dexscreener::issolanaaddress(&row.address) =>
+            {
+                Err(format!(
+                    "Row {}: {} is not an address",
+                    row.row, row.address
+                ))
+            }
+            row => row,
+        })
+        .collect()
+}
+
+/// Split a CSV line into trimmed fields. Quoted fields can contain commas, and `""` inside them is a quote.
+fn csvfields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(character) = chars.next() {
+        match character {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(character),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Write a `chain,address,alias` row, quoting fields that would otherwise be read differently.
+fn csvline(chain: &str, address: &str, alias: &str) -> String {
+    let field = |value: &str| {
+        if value.contains([',', '"', '\n', '\r']) || value.trim() != value {
+            format!(
+                "\"{}\"",
+                value.replace('"', "\"\"").replace(['\n', '\r'], " ")
+            )
+        } else {
+            value.to_string()
+        }
+    };
+    format!("{},{},{}", field(chain), field(address), field(alias))
+}
+
+/// Read the tokens of a token list. The chain is taken from `extensions.chain`, or from the chain id.
+fn parsetokenlist(content: &str) -> Result<Vec<Result<ImportRow, String>>, Error> {
+    let tokenlist: TokenList = serde_json::from_str(content)?;
+    Ok(tokenlist
+        .tokens
+        .into_iter()
+        .enumerate()
+        .map(|(index, token)| {
+            let chain = token.extensions.and_then(|x| x.chain).or_else(|| {
+                CHAINIDS
+                    .iter()
+                    .find(|(chainid, _)| *chainid == token.chain_id)
+                    .map(|(_, name)| (*name).to_string())
+            });
+            match chain {
+                Some(chain) => Ok(ImportRow {
+                    row: index + 1,
+                    chain,
+                    address: token.address,
+                    alias: Some(token.symbol).filter(|x| !x.is_empty()),
+                }),
+                None => Err(format!(
+                    "Row {}: chain id {} is not supported",
+                    index + 1,
+                    token.chain_id
+                )),
+            }
+        })
+        .collect())
+}
+
+/// Check a token that is added from a file, which can be edited by hand.
+/// The address has to be valid and available on Dexscreener, on `chain` when it is known.
+/// Everyone but the bot owner also can not add a token that uses the name of a global or verified token with another address.
+/// Returns the name of the token, the alias or else the symbol, and its pair with the most volume.
+pub fn checkfiletoken<'a>(
+    coins: &[Coins],
+    pairs: &'a [Pair],
+    chain: Option<&str>,
+    address: &str,
+    alias: Option<&str>,
+    ownercheck: bool,
+) -> Result<(String, &'a Pair), String> {
+    if !dexscreener::isevmaddress(address) && !dexscreener::issolanaaddress(address) {
+        return Err(format!("{address} is not an address"));
+    }
+    let Some(pair) = bestpairon(pairs, chain, address) else {
+        return Err(match chain {
+            Some(chain) => format!("{address} is not available on Dexscreener on {chain}"),
+            None => format!("{address} is not available on Dexscreener"),
+        });
+    };
+    let name = alias.map_or(pair.base_token.symbol.clone(), str::to_string);
+    if !ownercheck {
+        if let Some(lookalike) = findlookalike(
+            coins,
+            &name,
+            &pair.base_token.name,
+            &pair.base_token.address,
+        ) {
+            return Err(format!(
+                "{name} uses the name of {} with a different address",
+                lookalike.name
+            ));
+        }
+    }
+    Ok((name, pair))
+}
+
+/// The pair with the most volume of a token, only on `chain` when it is given.
+fn bestpairon<'a>(pairs: &'a [Pair], chain: Option<&str>, address: &str) -> Option<&'a Pair> {
+    let volume = |pair: &Pair| pair.volume.as_ref().and_then(|x| x.h24).unwrap_or(0.0);
+    pairs
+        .iter()
+        .filter(|pair| {
+            chain.is_none_or(|chain| pair.chain_id == chain)
+                && pair.base_token.address.eq_ignore_ascii_case(address)
+        })
+        .max_by(|a, b| volume(a).total_cmp(&volume(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVMADDRESS: &str = "0xc3864f98f2a61a7caeb95b039d031b4e2f55e0e9";
+    const SOLANAADDRESS: &str = "So11111111111111111111111111111111111111112";
+
+    fn rows(content: &str) -> Vec<(usize, String, String, Option<String>)> {
+        parsecsv(content)
+            .into_iter()
+            .flatten()
+            .map(|row| (row.row, row.chain, row.address, row.alias))
+            .collect()
+    }
+
+    #[test]
+    fn csvheaderandemptylines() {
+        let content = format!("chain,address,alias\n\nOptimism,{EVMADDRESS},OPENX\n");
+        assert_eq!(
+            rows(&content),
+            vec![(
+                3,
+                "optimism".to_string(),
+                EVMADDRESS.to_string(),
+                Some("OPENX".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn byteordermark() -> Result<(), Error> {
+        let content = format!("\u{feff}chain,address,alias\noptimism,{EVMADDRESS},OPENX");
+        let parsed = readrows("tokens.csv", &content)?;
+        assert_eq!(parsed.len(), 1);
+        assert!(parsed[0].is_ok());
+        let content = format!(
+            "\u{feff}{{ \"name\": \"Test\", \"tokens\": [{{ \"chainId\": 10, \"address\": \"{EVMADDRESS}\", \"symbol\": \"OPENX\" }}] }}"
+        );
+        assert_eq!(readrows("tokens.txt", &content)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn csvemptyalias() {
+        let content = format!("optimism,{EVMADDRESS}\nsolana,{SOLANAADDRESS},\n");
+        assert_eq!(
+            rows(&content),
+            vec![
+                (1, "optimism".to_string(), EVMADDRESS.to_string(), None),
+                (2, "solana".to_string(), SOLANAADDRESS.to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn csvquotedcomma() {
+        let content = format!("optimism,{EVMADDRESS},\"Open, \"\"X\"\"\"");
+        assert_eq!(
+            rows(&content),
+            vec![(
+                1,
+                "optimism".to_string(),
+                EVMADDRESS.to_string(),
+                Some("Open, \"X\"".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn csvinvalidrows() {
+        let content = format!("optimism,notanaddress,X\noptimism,{EVMADDRESS},a,b");
+        let parsed = parsecsv(&content);
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn csvroundtrip() {
+        for alias in ["OPENX", "Open, X", "\"quoted\"", " spaced ", ""] {
+            let line = csvline("optimism", EVMADDRESS, alias);
+            let expected = Some(alias.trim().to_string()).filter(|x| !x.is_empty());
+            assert_eq!(
+                rows(&line),
+                vec![(1, "optimism".to_string(), EVMADDRESS.to_string(), expected)],
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn tokenlistchains() -> Result<(), Error> {
+        let content = format!(
+            r#"{{
+                "name": "Test",
+                "tokens": [
+                    {{ "chainId": 10, "address": "{EVMADDRESS}", "symbol": "OPENX" }},
+                    {{ "chainId": 0, "address": "{SOLANAADDRESS}", "symbol": "SOL", "extensions": {{ "chain": "solana" }} }},
+                    {{ "chainId": 8453, "address": "{EVMADDRESS}", "symbol": "", "extensions": {{ "chain": null }} }},
+                    {{ "chainId": 999999, "address": "{EVMADDRESS}", "symbol": "X" }}
+                ]
+            }}"#
+        );
+        let parsed = parsetokenlist(&content)?;
+        assert_eq!(parsed.len(), 4);
+        let valid: Vec<(usize, String, Option<String>)> = parsed
+            .iter()
+            .flatten()
+            .map(|row| (row.row, row.chain.clone(), row.alias.clone()))
+            .collect();
+        assert_eq!(
+            valid,
+            vec![
+                (1, "optimism".to_string(), Some("OPENX".to_string())),
+                (2, "solana".to_string(), Some("SOL".to_string())),
+                (3, "base".to_string(), None),
+            ]
+        );
+        assert!(parsed[3].is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn tokenlistinvalid() {
+        assert!(parsetokenlist("{ \"tokens\": 1 }").is_err());
+    }
+}
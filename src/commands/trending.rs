@@ -0,0 +1,195 @@
+use crate::dexscreener::{self, Pair, TokenBoost};
+use crate::formatting::{self, Locale};
+use crate::{Context, Error};
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter};
+use poise::{ChoiceParameter, CreateReply};
+use serenity::model::Colour;
+
+/// The amount of tokens shown in `/trending`.
+const MAXTOKENS: usize = 10;
+/// Discord allows at most 4096 characters in the description of an embed.
+const MAXDESCRIPTION: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum TrendingList {
+    #[name = "Top boosted"]
+    TopBoosts,
+    #[name = "Latest boosted"]
+    LatestBoosts,
+    #[name = "Latest profiles"]
+    LatestProfiles,
+}
+
+/// Show the tokens that are boosted or trending on Dexscreener.
+#[poise::command(slash_command)]
+pub async fn trending(
+    ctx: Context<'_>,
+    #[description = "Only show tokens on this chain, like solana or ethereum"] chain: Option<
+        String,
+    >,
+    #[description = "Which list to show, top boosted if not chosen"] list: Option<TrendingList>,
+) -> Result<(), Error> {
+    let list = list.unwrap_or(TrendingList::TopBoosts);
+    ctx.defer().await?;
+
+    let boosts = match list {
+        TrendingList::TopBoosts => dexscreener::topboosts().await?,
+        TrendingList::LatestBoosts => dexscreener::latestboosts().await?,
+        TrendingList::LatestProfiles => dexscreener::latestprofiles().await?,
+    };
+    let chain = chain.map(|x| x.trim().to_lowercase());
+    let boosts: Vec<TokenBoost> = boosts
+        .into_iter()
+        .filter(|boost| chain.is_none() || chain.as_ref() == Some(&boost.chain_id))
+        .take(MAXTOKENS)
+        .collect();
+    if boosts.is_empty() {
+        ctx.say("There are no tokens in this list for that chain right now")
+            .await?;
+        return Ok(());
+    }
+
+    // Boosts only contain the address, so fetch the pairs to show the symbol and price
+    let addresses: Vec<String> = boosts
+        .iter()
+        .map(|boost| boost.token_address.clone())
+        .collect();
+    let pairs = dexscreener::bestpairs(&addresses).await?;
+    let locale = Locale::from_env();
+
+    let lines: Vec<String> = boosts
+        .iter()
+        .enumerate()
+        .map(|(index, boost)| {
+            let pair = pairs.get(&boost.token_address.to_lowercase());
+            let mut parts = vec![format!(
+                "**{}. [{}]({})** on {}",
+                index + 1,
+                tokenname(boost, pair),
+                boost.url,
+                boost.chain_id
+            )];
+            if let Some(price) = pair.and_then(|x| x.price_usd.as_ref()) {
+                parts.push(formatting::pricestr(price, locale));
+            }
+            if let Some(totalamount) = boost.total_amount {
+                parts.push(format!("⚡ {totalamount}"));
+            }
+            let line = parts.join(" · ");
+            let links = boostlinks(boost);
+            if links.is_empty() {
+                line
+            } else {
+                format!("{line}\n{links}")
+            }
+        })
+        .collect();
+
+    let embed = CreateEmbed::default()
+        .title(format!(
+            "{}{}",
+            list.name(),
+            chain.map(|x| format!(" on {x}")).unwrap_or_default()
+        ))
+        .description(cappedlines(&lines))
+        .footer(CreateEmbedFooter::new(
+            "All rights reserved to Dexscreener.com",
+        ))
+        .colour(Colour::from_rgb(255, 200, 0));
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Create the embed posted in a feed channel when a token is boosted.
+#[cfg(feature = "database")]
+pub fn boostcard(boost: &TokenBoost, pair: Option<&Pair>) -> CreateEmbed {
+    let locale = Locale::from_env();
+    let mut embed = CreateEmbed::default()
+        .title(format!(
+            "⚡ {} was boosted on {}",
+            tokenname(boost, pair),
+            boost.chain_id
+        ))
+        .url(boost.url.clone())
+        .description(format!(
+            "`{}`\n{}",
+            boost.token_address,
+            boost.description.clone().unwrap_or_default()
+        ))
+        .footer(CreateEmbedFooter::new(
+            "All rights reserved to Dexscreener.com",
+        ))
+        .colour(Colour::from_rgb(255, 200, 0));
+    if let Some(amount) = boost.amount {
+        embed = embed.field("Boost", amount.to_string(), true);
+    }
+    if let Some(totalamount) = boost.total_amount {
+        embed = embed.field("Total boosts", totalamount.to_string(), true);
+    }
+    if let Some(price) = pair.and_then(|x| x.price_usd.as_ref()) {
+        embed = embed.field("Price", formatting::pricestr(price, locale), true);
+    }
+    if let Some(liquidity) = pair.and_then(|x| x.liquidity.as_ref()?.usd) {
+        embed = embed.field("Liquidity", formatting::compactusd(liquidity, locale), true);
+    }
+    let links = boostlinks(boost);
+    if !links.is_empty() {
+        embed = embed.field("Links", links, false);
+    }
+    if let Some(icon) = boost.icon.as_ref().filter(|x| x.starts_with("https://")) {
+        embed = embed.thumbnail(icon);
+    }
+    embed
+}
+
+/// Join the lines of the list, leaving out the last tokens when they do not fit in the description.
+fn cappedlines(lines: &[String]) -> String {
+    let mut description: Vec<&str> = vec![];
+    let mut length = 0;
+    for (index, line) in lines.iter().enumerate() {
+        let more = format!("…and {} more", lines.len() - index);
+        // Every line after the first is preceded by a newline
+        if length + line.chars().count() + more.chars().count() + 2 > MAXDESCRIPTION {
+            return format!("{}\n{more}", description.join("\n"))
+                .trim_start()
+                .to_string();
+        }
+        length += line.chars().count() + 1;
+        description.push(line);
+    }
+    description.join("\n")
+}
+
+/// The symbol of a boosted token if its pair is known, otherwise a shortened address.
+fn tokenname(boost: &TokenBoost, pair: Option<&Pair>) -> String {
+    match pair {
+        Some(pair) => pair.base_token.symbol.clone(),
+        None => {
+            let address = &boost.token_address;
+            if address.is_ascii() && address.len() > 10 {
+                format!("{}…{}", &address[..6], &address[address.len() - 4..])
+            } else {
+                address.clone()
+            }
+        }
+    }
+}
+
+/// The links of a token profile as markdown, e.g. `[Website](https://...) · [Twitter](https://...)`.
+fn boostlinks(boost: &TokenBoost) -> String {
+    boost
+        .links
+        .iter()
+        .flatten()
+        .filter(|link| link.url.starts_with("https://"))
+        .map(|link| {
+            let label = link
+                .label
+                .clone()
+                .or(link.linktype.clone())
+                .unwrap_or("Link".to_string());
+            format!("[{label}]({})", link.url)
+        })
+        .collect::<Vec<String>>()
+        .join(" · ")
+}
@@ -0,0 +1,153 @@
+//! Connecting to the database, signing in and reconnecting when the connection is lost.
+#![cfg(feature = "database")]
+use crate::{Error, DB};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use surrealdb::opt::auth::{Database, Namespace, Root};
+
+/// Seconds between two health checks while the database is available.
+const HEALTHINTERVAL: u64 = 30;
+/// The longest wait in seconds between two reconnect attempts.
+const MAXBACKOFF: u64 = 300;
+
+/// The first connection succeeded. The connection can not be opened twice, the client reconnects by itself afterwards.
+static CONNECTED: AtomicBool = AtomicBool::new(false);
+/// The last connection attempt or health check succeeded.
+static AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Check if the database can be used right now.
+/// Commands that need the database tell the user to try again later when it is not.
+pub fn available() -> bool {
+    AVAILABLE.load(Ordering::Relaxed)
+}
+
+/// Try to connect to the database once before the bot starts.
+/// The bot also starts when this fails, `keepconnected` keeps trying in the background.
+pub async fn createdatabase() {
+    println!("Creating database");
+    match connect().await {
+        Ok(()) => println!("Connected to the database"),
+        Err(dberror) => println!(
+            "Could not connect to the database, starting without it and trying again in the background: {dberror}"
+        ),
+    }
+}
+
+/// Check the connection every 30 seconds, and reconnect with a backoff of 1 second up to 5 minutes when it is lost.
+pub async fn keepconnected() {
+    let mut backoff: u64 = 1;
+    loop {
+        if available() {
+            tokio::time::sleep(Duration::from_secs(HEALTHINTERVAL)).await;
+            if let Err(healtherror) = DB.health().await {
+                println!("Lost the connection to the database: {healtherror}");
+                AVAILABLE.store(false, Ordering::Relaxed);
+                backoff = 1;
+            }
+            continue;
+        }
+        tokio::time::sleep(Duration::from_secs(backoff)).await;
+        match connect().await {
+            Ok(()) => {
+                println!("Reconnected to the database");
+                backoff = 1;
+                if let Err(refresherror) = crate::tokenindex::refresh().await {
+                    println!("Could not refresh the token index: {refresherror}");
+                }
+            }
+            Err(dberror) => {
+                backoff = (backoff * 2).min(MAXBACKOFF);
+                println!("Could not reconnect to the database, trying again in {backoff} seconds: {dberror}");
+            }
+        }
+    }
+}
+
+/// Connect if that did not happen yet, sign in, choose the namespace and database and apply the migrations.
+async fn connect() -> Result<(), Error> {
+    if !CONNECTED.load(Ordering::Relaxed) {
+        let address = databaseurl();
+        println!("Connecting to the database on address: {address}");
+        DB.connect(address).await?;
+        CONNECTED.store(true, Ordering::Relaxed);
+    }
+    DB.health().await?;
+    let namespace = std::env::var("DATABASE_NAMESPACE").unwrap_or("dexscreener".to_string());
+    let database = std::env::var("DATABASE_NAME").unwrap_or("dexscreenerdb".to_string());
+    signin(&namespace, &database).await?;
+    DB.use_ns(namespace).use_db(database).await?;
+    crate::migrations::migrate().await?;
+    AVAILABLE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Sign in with `DATABASE_USER` and `DATABASE_PASS` when a user is set.
+/// `DATABASE_AUTH_LEVEL` chooses if the user is a `root` (default), `namespace` or `database` user.
+async fn signin(namespace: &str, database: &str) -> Result<(), Error> {
+    let Some(username) = secret("DATABASE_USER")? else {
+        return Ok(());
+    };
+    let password = secret("DATABASE_PASS")?.unwrap_or_default();
+    let (username, password) = (username.as_str(), password.as_str());
+    let level = std::env::var("DATABASE_AUTH_LEVEL").unwrap_or("root".to_string());
+    match level.trim().to_lowercase().as_str() {
+        "root" => DB.signin(Root { username, password }).await?,
+        "namespace" => {
+            DB.signin(Namespace {
+                namespace,
+                username,
+                password,
+            })
+            .await?
+        }
+        "database" => {
+            DB.signin(Database {
+                namespace,
+                database,
+                username,
+                password,
+            })
+            .await?
+        }
+        _ => {
+            return Err(format!(
+                "DATABASE_AUTH_LEVEL is {level}, but should be root, namespace or database"
+            )
+            .into())
+        }
+    };
+    Ok(())
+}
+
+/// Read a secret from the variable `name`, or from the file in `name_FILE` (e.g. a Docker secret).
+fn secret(name: &str) -> Result<Option<String>, Error> {
+    if let Ok(val) = std::env::var(name) {
+        return Ok(Some(val));
+    }
+    match std::env::var(format!("{name}_FILE")) {
+        Ok(path) => match std::fs::read_to_string(&path) {
+            Ok(val) => Ok(Some(val.trim_end().to_string())),
+            Err(readerror) => {
+                Err(format!("Could not read {name}_FILE ({path}): {readerror}").into())
+            }
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+/// The address of the database, e.g. `mem://`, `file://dexscreener.db`, `rocksdb://data`, `wss://example.com` or `https://example.com`.
+/// `SURREAL_BIND` is still read when `DATABASE_URL` is not set, an address without a scheme is a websocket address.
+/// Without either, `memdatabase` and `filedatabase` choose the default, otherwise it is a local server.
+fn databaseurl() -> String {
+    let address = match std::env::var("DATABASE_URL").or(std::env::var("SURREAL_BIND")) {
+        Ok(val) => val.trim().to_string(),
+        Err(_) if cfg!(feature = "memdatabase") => "mem://".to_string(),
+        Err(_) if cfg!(feature = "filedatabase") => "file://dexscreener.db".to_string(),
+        Err(_) => "ws://localhost:8000".to_string(),
+    };
+    if address.contains("://") {
+        address
+    } else {
+        format!("ws://{address}")
+    }
+}
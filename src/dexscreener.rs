@@ -0,0 +1,229 @@
+//! Client for the Dexscreener API and the structs it returns.
+#![allow(clippy::struct_field_names)]
+#![allow(clippy::module_name_repetitions)]
+use crate::Error;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Dexscreener accepts up to 30 addresses in one token request.
+const MAXADDRESSESPERREQUEST: usize = 30;
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+/// Fetch all pairs that have the supplied address as one of their tokens.
+pub async fn tokenpairs(address: &str) -> Result<Vec<Pair>, Error> {
+    let url = format!("https://api.dexscreener.com/latest/dex/tokens/{address}");
+    let result = CLIENT.get(url).send().await?;
+    let parsedresult = result.json::<Root>().await?;
+    Ok(parsedresult.pairs.unwrap_or_default())
+}
+
+/// Fetch a single pair by its chain and pair address.
+pub async fn pair(chainid: &str, pairaddress: &str) -> Result<Option<Pair>, Error> {
+    let url = format!("https://api.dexscreener.com/latest/dex/pairs/{chainid}/{pairaddress}");
+    let result = CLIENT.get(url).send().await?;
+    let parsedresult = result.json::<Root>().await?;
+    Ok(parsedresult.pairs.unwrap_or_default().into_iter().next())
+}
+
+/// Get the pair with the most volume in the last 24 hours for a token address.
+pub async fn bestpair(address: &str) -> Result<Option<Pair>, Error> {
+    let mut pairs = tokenpairs(address).await?;
+    sortbyvolume(&mut pairs);
+    Ok(pairs.pop())
+}
+
+/// Fetch all pairs of multiple token addresses, fetching 30 addresses per request.
+pub async fn tokenspairs(addresses: &[String]) -> Result<Vec<Pair>, Error> {
+    let mut pairs = vec![];
+    for chunk in addresses.chunks(MAXADDRESSESPERREQUEST) {
+        pairs.extend(tokenpairs(&chunk.join(",")).await?);
+    }
+    Ok(pairs)
+}
+
+/// Get the pair with the most volume in the last 24 hours for each address, fetching 30 addresses per request.
+/// The keys are the lowercase addresses, as the casing of an address can differ between the request and the response.
+pub async fn bestpairs(addresses: &[String]) -> Result<HashMap<String, Pair>, Error> {
+    let volume = |pair: &Pair| pair.volume.as_ref().and_then(|x| x.h24).unwrap_or(0.0);
+    let mut bestpairs: HashMap<String, Pair> = HashMap::new();
+    for pair in tokenspairs(addresses).await? {
+        let address = pair.base_token.address.to_lowercase();
+        match bestpairs.get(&address) {
+            Some(currentpair) if volume(currentpair) >= volume(&pair) => {}
+            _ => {
+                bestpairs.insert(address, pair);
+            }
+        }
+    }
+    Ok(bestpairs)
+}
+
+/// Fetch the tokens that were boosted most recently.
+pub async fn latestboosts() -> Result<Vec<TokenBoost>, Error> {
+    let result = CLIENT
+        .get("https://api.dexscreener.com/token-boosts/latest/v1")
+        .send()
+        .await?;
+    Ok(result.json::<Vec<TokenBoost>>().await?)
+}
+
+/// Fetch the tokens with the most active boosts.
+pub async fn topboosts() -> Result<Vec<TokenBoost>, Error> {
+    let result = CLIENT
+        .get("https://api.dexscreener.com/token-boosts/top/v1")
+        .send()
+        .await?;
+    Ok(result.json::<Vec<TokenBoost>>().await?)
+}
+
+/// Fetch the tokens that most recently got a profile on Dexscreener.
+pub async fn latestprofiles() -> Result<Vec<TokenBoost>, Error> {
+    let result = CLIENT
+        .get("https://api.dexscreener.com/token-profiles/latest/v1")
+        .send()
+        .await?;
+    Ok(result.json::<Vec<TokenBoost>>().await?)
+}
+
+/// Sort pairs from least to most volume in the last 24 hours.
+#[allow(clippy::cast_possible_truncation)]
+pub fn sortbyvolume(pairs: &mut [Pair]) {
+    pairs.sort_by_key(|x| {
+        x.volume
+            .clone()
+            .unwrap_or_default()
+            .h24
+            .unwrap_or(0.0)
+            .round() as i64
+    });
+}
+
+/// Find every EVM (`0x` + 40 hex characters) and Solana (base58, 32 to 44 characters) address in a text.
+/// Duplicates are removed, the order of appearance is kept.
+pub fn findaddresses(text: &str) -> Vec<String> {
+    let mut addresses: Vec<String> = vec![];
+    for word in text.split(|x: char| !x.is_ascii_alphanumeric()) {
+        if (isevmaddress(word) || issolanaaddress(word)) && !addresses.iter().any(|x| x == word) {
+            addresses.push(word.to_string());
+        }
+    }
+    addresses
+}
+
+/// Check if a word is an EVM address, without validating the checksum.
+pub fn isevmaddress(word: &str) -> bool {
+    word.len() == 42 && word.starts_with("0x") && word[2..].chars().all(|x| x.is_ascii_hexdigit())
+}
+
+/// Check if a word looks like a Solana address.
+/// Base58 can also match long normal words, so both upper and lower case letters are required.
+pub fn issolanaaddress(word: &str) -> bool {
+    (32..=44).contains(&word.len())
+        && word
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() && !matches!(x, '0' | 'O' | 'I' | 'l'))
+        && word.chars().any(|x| x.is_ascii_uppercase())
+        && word.chars().any(|x| x.is_ascii_lowercase())
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Root {
+    pub schema_version: String,
+    pub pairs: Option<Vec<Pair>>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pair {
+    pub chain_id: String,
+    pub dex_id: String,
+    pub url: String,
+    pub pair_address: String,
+    pub base_token: BaseToken,
+    pub quote_token: Option<BaseToken>,
+    pub price_usd: Option<String>,
+    pub txns: Option<Txns>,
+    pub volume: Option<Volume>,
+    pub price_change: Option<PriceChange>,
+    pub liquidity: Option<Liquidity>,
+    pub fdv: Option<f64>,
+    pub market_cap: Option<f64>,
+    /// Unix timestamp in milliseconds
+    pub pair_created_at: Option<i64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BaseToken {
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Txns {
+    pub m5: Option<BuysSells>,
+    pub h1: Option<BuysSells>,
+    pub h6: Option<BuysSells>,
+    pub h24: Option<BuysSells>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuysSells {
+    pub buys: u64,
+    pub sells: u64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Volume {
+    pub m5: Option<f64>,
+    pub h1: Option<f64>,
+    pub h6: Option<f64>,
+    pub h24: Option<f64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceChange {
+    pub m5: Option<f64>,
+    pub h1: Option<f64>,
+    pub h6: Option<f64>,
+    pub h24: Option<f64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Liquidity {
+    pub usd: Option<f64>,
+    pub base: Option<f64>,
+    pub quote: Option<f64>,
+}
+
+/// A boosted token or a token profile. Profiles have the same shape, without the boost amounts.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBoost {
+    pub url: String,
+    pub chain_id: String,
+    pub token_address: String,
+    pub amount: Option<f64>,
+    pub total_amount: Option<f64>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub links: Option<Vec<Link>>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Link {
+    #[serde(rename = "type")]
+    pub linktype: Option<String>,
+    pub label: Option<String>,
+    pub url: String,
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "database")]
+use crate::commands::pricecard::{compactcard, pricebuttons};
+use crate::settings::dbstructs::AutoDetect;
+use crate::{dexscreener, Error, DB};
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateAllowedMentions, CreateMessage, Message,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Seconds between two automatic replies in the same channel if `MESSAGE_DETECTION_COOLDOWN` is not set.
+const DEFAULTCOOLDOWN: u64 = 30;
+/// The maximum amount of price cards in one automatic reply.
+const MAXCARDS: usize = 3;
+
+/// When the bot last replied automatically in a channel.
+static LASTREPLIES: Lazy<Mutex<HashMap<ChannelId, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Reply with a compact price card to messages containing contract addresses.
+/// Only replies in channels that opted in, and at most once per cooldown in each channel.
+pub async fn autodetect(ctx: &serenity::Context, message: &Message) -> Result<(), Error> {
+    if message.author.bot {
+        return Ok(());
+    }
+    let Some(guildid) = message.guild_id else {
+        return Ok(());
+    };
+    // Only go to the database when there is something to price
+    let addresses = dexscreener::findaddresses(&message.content);
+    if addresses.is_empty() || oncooldown(message.channel_id) {
+        return Ok(());
+    }
+    let autodetect: Option<AutoDetect> = DB.select(("autodetect", guildid.to_string())).await?;
+    match autodetect {
+        Some(settings) if settings.enabled && settings.channels.contains(&message.channel_id) => {}
+        _ => return Ok(()),
+    }
+    // Claimed before fetching, so a second message that arrives while fetching does not get a reply as well
+    if !claimreply(message.channel_id) {
+        return Ok(());
+    }
+
+    // Every card gets its own row of buttons, in the same order as the cards
+    let addresses: Vec<String> = addresses.into_iter().take(MAXCARDS).collect();
+    let mut pairs = match dexscreener::bestpairs(&addresses).await {
+        Ok(pairs) => pairs,
+        Err(fetcherror) => {
+            println!("Could not fetch {addresses:?} from Dexscreener: {fetcherror}");
+            return Ok(());
+        }
+    };
+    let mut embeds = vec![];
+    let mut buttons = vec![];
+    for address in &addresses {
+        if let Some(pair) = pairs.remove(&address.to_lowercase()) {
+            embeds.push(compactcard(&pair));
+            buttons.push(pricebuttons(&pair));
+        }
+    }
+    if embeds.is_empty() {
+        return Ok(());
+    }
+
+    message
+        .channel_id
+        .send_message(
+            ctx,
+            CreateMessage::new()
+                .embeds(embeds)
+                .components(buttons)
+                .reference_message(message)
+                .allowed_mentions(CreateAllowedMentions::new()),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Check if the bot replied in this channel less than a cooldown ago.
+fn oncooldown(channelid: ChannelId) -> bool {
+    lastreplies()
+        .get(&channelid)
+        .is_some_and(|lastreply| lastreply.elapsed() < cooldown())
+}
+
+/// Record a reply in this channel if it is not on cooldown, checked and recorded under one lock.
+/// Returns false when the channel is on cooldown.
+fn claimreply(channelid: ChannelId) -> bool {
+    let cooldown = cooldown();
+    let mut lastreplies = lastreplies();
+    if lastreplies
+        .get(&channelid)
+        .is_some_and(|lastreply| lastreply.elapsed() < cooldown)
+    {
+        return false;
+    }
+    lastreplies.insert(channelid, Instant::now());
+    true
+}
+
+fn cooldown() -> Duration {
+    let cooldown = match std::env::var("MESSAGE_DETECTION_COOLDOWN") {
+        Ok(val) => val.parse::<u64>().unwrap_or(DEFAULTCOOLDOWN),
+        Err(_) => DEFAULTCOOLDOWN,
+    };
+    Duration::from_secs(cooldown)
+}
+
+/// Lock the last replies, a panic while holding the lock can not leave the map in a broken state.
+fn lastreplies() -> std::sync::MutexGuard<'static, HashMap<ChannelId, Instant>> {
+    match LASTREPLIES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
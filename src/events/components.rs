@@ -0,0 +1,130 @@
+use crate::commands::pricecard::{
+    compactcard, detailcard, newpaircard, parsebuttonid, pricecard, withrisk, withverified,
+    PriceButton, NEWPAIRFOOTER, VERIFIEDFIELD,
+};
+use crate::{dexscreener, risk, Error};
+use poise::serenity_prelude::{
+    self as serenity, ComponentInteraction, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+
+/// Handle clicks on the buttons of price cards.
+/// These are not collected per message, so buttons on old messages keep working after a restart.
+pub async fn pricebuttons(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+) -> Result<(), Error> {
+    let Some((button, chainid, pairaddress)) = parsebuttonid(&interaction.data.custom_id) else {
+        return Ok(());
+    };
+
+    let pair = match dexscreener::pair(chainid, pairaddress).await {
+        Ok(Some(pair)) => pair,
+        Ok(None) => {
+            return ephemeral(
+                ctx,
+                interaction,
+                "This pair is no longer available on Dexscreener",
+            )
+            .await;
+        }
+        Err(fetcherror) => {
+            println!("Could not fetch {chainid}/{pairaddress} from Dexscreener: {fetcherror}");
+            return ephemeral(
+                ctx,
+                interaction,
+                "Dexscreener could not be reached, please try again later",
+            )
+            .await;
+        }
+    };
+
+    let response = match button {
+        // Only the embed is replaced, so other components like a select menu stay on the message
+        PriceButton::Refresh => {
+            let embeds = &interaction.message.embeds;
+            // Cards that link to the pair are rebuilt by the builder that created them:
+            // automatic replies can hold several compact cards and only the card of this pair is refreshed,
+            // cards of new pair feeds are refreshed as such and market alerts are kept with a compact card of now below them
+            let ispaircard =
+                |embed: &serenity::Embed| embed.url.as_deref() == Some(pair.url.as_str());
+            if embeds.iter().any(ispaircard) {
+                let mut refreshedcard = false;
+                let mut refreshed: Vec<CreateEmbed> = embeds
+                    .iter()
+                    .map(|embed| {
+                        if !ispaircard(embed) {
+                            CreateEmbed::from(embed.clone())
+                        } else if embed
+                            .footer
+                            .as_ref()
+                            .is_some_and(|footer| footer.text == NEWPAIRFOOTER)
+                        {
+                            refreshedcard = true;
+                            newpaircard(&pair)
+                        } else if embed.description.is_none() {
+                            refreshedcard = true;
+                            compactcard(&pair)
+                        } else {
+                            CreateEmbed::from(embed.clone())
+                        }
+                    })
+                    .collect();
+                if !refreshedcard {
+                    refreshed.push(compactcard(&pair));
+                }
+                let response = CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new().embeds(refreshed),
+                );
+                interaction.create_response(ctx, response).await?;
+                return Ok(());
+            }
+            let mut embed = pricecard(&pair);
+            // Keep the verified badge and the risk assessment on cards that had one
+            let fieldnames: Vec<&str> = interaction
+                .message
+                .embeds
+                .iter()
+                .flat_map(|embed| embed.fields.iter().map(|field| field.name.as_str()))
+                .collect();
+            if fieldnames.iter().any(|name| name.starts_with("Risk")) {
+                embed = withrisk(embed, &risk::assess(&pair, &risk::knowntokens().await));
+            }
+            if fieldnames.contains(&VERIFIEDFIELD) {
+                embed = withverified(embed);
+            }
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new().embed(embed),
+            )
+        }
+        PriceButton::Details => CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .embed(withrisk(
+                    detailcard(&pair),
+                    &risk::assess(&pair, &risk::knowntokens().await),
+                ))
+                .ephemeral(true),
+        ),
+    };
+    interaction.create_response(ctx, response).await?;
+    Ok(())
+}
+
+/// Respond to an interaction with a message only the user can see.
+async fn ephemeral(
+    ctx: &serenity::Context,
+    interaction: &ComponentInteraction,
+    text: &str,
+) -> Result<(), Error> {
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(text)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+    Ok(())
+}
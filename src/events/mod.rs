@@ -0,0 +1,34 @@
+pub mod autodetect;
+pub mod components;
+
+use crate::{Data, Error};
+use poise::serenity_prelude as serenity;
+
+/// Handle the events from Discord that are not commands.
+pub async fn eventhandler(
+    ctx: &serenity::Context,
+    event: &serenity::FullEvent,
+    _framework: poise::FrameworkContext<'_, Data, Error>,
+    _data: &Data,
+) -> Result<(), Error> {
+    match event {
+        #[cfg(feature = "database")]
+        serenity::FullEvent::Message { new_message } => {
+            autodetect::autodetect(ctx, new_message).await?;
+        }
+        serenity::FullEvent::InteractionCreate {
+            interaction: serenity::Interaction::Component(interaction),
+        } if interaction.data.custom_id.starts_with("price:") => {
+            components::pricebuttons(ctx, interaction).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Check if message detection is turned on with `MESSAGE_DETECTION`.
+/// This requests the intents to read messages, which also have to be turned on in the Discord developer portal.
+pub fn messagedetectionenabled() -> bool {
+    std::env::var("MESSAGE_DETECTION")
+        .is_ok_and(|val| val.eq_ignore_ascii_case("true") || val == "1")
+}
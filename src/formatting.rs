@@ -0,0 +1,365 @@
+//! Formatting of numbers shown in embeds.
+//!
+//! Dexscreener returns prices as raw strings, which makes micro-cap tokens unreadable (`$0.00000001234`).
+//! These functions turn prices, percentages and large amounts into short, readable strings:
+//! - Prices are shown with 4 significant digits, long runs of zeros are collapsed: `$0.0₇1234`
+//! - Percentages always have a sign and two decimals: `+12.34%`
+//! - Volume, liquidity and market cap are abbreviated: `$1.23M`
+#![allow(clippy::module_name_repetitions)]
+
+/// The number of significant digits shown for prices below 1.
+const SIGNIFICANTDIGITS: usize = 4;
+/// From this many zeros after the decimal point onwards, the zeros are collapsed into a subscript count.
+const SUBSCRIPTZEROS: usize = 4;
+/// Abbreviations used for large numbers, from large to small.
+const ABBREVIATIONS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+/// Separators used when writing numbers.
+/// The locale of the bot is read from `NUMBER_LOCALE` with `Locale::from_env`:
+/// - `en` (default): `1,234.56`
+/// - `eu`: `1.234,56`
+/// - `ch`: `1'234.56`
+/// - `fr`: `1 234,56`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub decimal: char,
+    pub thousands: char,
+}
+
+impl Locale {
+    pub const EN: Locale = Locale {
+        decimal: '.',
+        thousands: ',',
+    };
+    pub const EU: Locale = Locale {
+        decimal: ',',
+        thousands: '.',
+    };
+    pub const CH: Locale = Locale {
+        decimal: '.',
+        thousands: '\'',
+    };
+    pub const FR: Locale = Locale {
+        decimal: ',',
+        thousands: '\u{202f}',
+    };
+
+    /// Get the locale set in `NUMBER_LOCALE`, falling back to `en` if it is not set or unknown.
+    pub fn from_env() -> Locale {
+        match std::env::var("NUMBER_LOCALE") {
+            Ok(val) => Locale::from_name(&val).unwrap_or(Locale::EN),
+            Err(_) => Locale::EN,
+        }
+    }
+
+    /// Get a locale by its (case-insensitive) name.
+    pub fn from_name(name: &str) -> Option<Locale> {
+        match name.trim().to_lowercase().as_str() {
+            "en" | "us" | "uk" => Some(Locale::EN),
+            "eu" | "de" | "nl" | "it" | "es" => Some(Locale::EU),
+            "ch" => Some(Locale::CH),
+            "fr" => Some(Locale::FR),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::EN
+    }
+}
+
+/// Format a price in dollars.
+/// - Prices of 1 and above get two decimals and thousand separators: `$67,120.45`
+/// - Prices below 1 get 4 significant digits: `$0.4213`, `$0.001234`
+/// - Prices with 4 or more zeros after the decimal point get a subscript zero count: `$0.0₇1234`
+pub fn price(value: f64, locale: Locale) -> String {
+    if !value.is_finite() {
+        return "$N/A".to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    format!("{sign}${}", unsignedprice(value.abs(), locale))
+}
+
+/// Format a price string as returned by Dexscreener, keeping the original text if it is not a number.
+pub fn pricestr(value: &str, locale: Locale) -> String {
+    match value.trim().parse::<f64>() {
+        Ok(number) => price(number, locale),
+        Err(_) => format!("${value}"),
+    }
+}
+
+fn unsignedprice(value: f64, locale: Locale) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    if value >= 1.0 {
+        return thousands(value, 2, locale);
+    }
+    // Scientific notation rounds to the significant digits for us, e.g. `1.234e-8`.
+    let scientific = format!("{:.*e}", SIGNIFICANTDIGITS - 1, value);
+    let (mantissa, exponent) = match scientific.split_once('e') {
+        Some(val) => val,
+        None => return thousands(value, 2, locale),
+    };
+    let exponent = match exponent.parse::<i32>() {
+        Ok(val) => val,
+        Err(_) => return thousands(value, 2, locale),
+    };
+    // Rounding may carry over, e.g. 0.99996 becomes `1.000e0`
+    if exponent >= 0 {
+        return thousands(value, 2, locale);
+    }
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let digits = digits.trim_end_matches('0');
+    let zeros = usize::try_from(-exponent - 1).unwrap_or(0);
+
+    if zeros >= SUBSCRIPTZEROS {
+        format!("0{}0{}{digits}", locale.decimal, subscript(zeros))
+    } else {
+        format!("0{}{}{digits}", locale.decimal, "0".repeat(zeros))
+    }
+}
+
+/// Format a percentage change with a sign and two decimals: `+12.34%`, `-0.50%`.
+pub fn percentage(value: f64, locale: Locale) -> String {
+    if !value.is_finite() {
+        return "N/A%".to_string();
+    }
+    let formatted = thousands(value.abs(), 2, locale);
+    // A change that rounds to zero is shown as `+0.00%`, not `-0.00%`
+    let sign = if value < 0.0 && formatted.chars().any(|x| x.is_ascii_digit() && x != '0') {
+        "-"
+    } else {
+        "+"
+    };
+    format!("{sign}{formatted}%")
+}
+
+/// Abbreviate a large amount with K/M/B/T, keeping at most two decimals: `1.23M`, `950`.
+pub fn compact(value: f64, locale: Locale) -> String {
+    if !value.is_finite() {
+        return "N/A".to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    let abs = value.abs();
+    for (size, suffix) in ABBREVIATIONS {
+        // Also use the larger unit when the smaller one rounds up to 1000, so 999_999 becomes `1M` instead of `1000K`
+        if abs >= size || (abs / size * 100_000.0).round() / 100.0 >= 1000.0 {
+            let scaled = abs / size;
+            if scaled >= 1000.0 && suffix == "T" {
+                return format!("{sign}{}{suffix}", thousands(scaled, 0, locale));
+            }
+            return format!(
+                "{sign}{}{suffix}",
+                trimdecimals(&decimal(scaled, 2, locale), locale)
+            );
+        }
+    }
+    format!("{sign}{}", trimdecimals(&decimal(abs, 2, locale), locale))
+}
+
+/// Abbreviate an amount in dollars: `$1.23M`.
+pub fn compactusd(value: f64, locale: Locale) -> String {
+    let formatted = compact(value, locale);
+    match formatted.strip_prefix('-') {
+        Some(val) => format!("-${val}"),
+        None => format!("${formatted}"),
+    }
+}
+
+/// Write a number with a fixed amount of decimals and thousand separators: `67,120.45`.
+pub fn thousands(value: f64, decimals: usize, locale: Locale) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut grouped = String::with_capacity(integer.len() + integer.len() / 3);
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(locale.thousands);
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if value < 0.0 && formatted.chars().any(|x| x.is_ascii_digit() && x != '0') {
+        "-"
+    } else {
+        ""
+    };
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped}{}{fraction}", locale.decimal),
+        None => format!("{sign}{grouped}"),
+    }
+}
+
+/// Write a number with a fixed amount of decimals without thousand separators.
+fn decimal(value: f64, decimals: usize, locale: Locale) -> String {
+    format!("{value:.decimals$}").replace('.', &locale.decimal.to_string())
+}
+
+/// Remove trailing zeros after the decimal separator: `1.50` becomes `1.5`, `2.00` becomes `2`.
+fn trimdecimals(value: &str, locale: Locale) -> String {
+    if !value.contains(locale.decimal) {
+        return value.to_string();
+    }
+    value
+        .trim_end_matches('0')
+        .trim_end_matches(locale.decimal)
+        .to_string()
+}
+
+/// Write a number with subscript digits: 12 becomes `₁₂`.
+fn subscript(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .map(|digit| match digit {
+            '0' => '₀',
+            '1' => '₁',
+            '2' => '₂',
+            '3' => '₃',
+            '4' => '₄',
+            '5' => '₅',
+            '6' => '₆',
+            '7' => '₇',
+            '8' => '₈',
+            _ => '₉',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pricesabove1() {
+        assert_eq!(price(67_120.45, Locale::EN), "$67,120.45");
+        assert_eq!(price(1.0, Locale::EN), "$1.00");
+        assert_eq!(price(1_234_567.891, Locale::EN), "$1,234,567.89");
+    }
+
+    #[test]
+    fn pricesbelow1() {
+        assert_eq!(price(0.4213, Locale::EN), "$0.4213");
+        assert_eq!(price(0.001_234, Locale::EN), "$0.001234");
+        assert_eq!(price(0.000_123_4, Locale::EN), "$0.0001234");
+        assert_eq!(price(0.5, Locale::EN), "$0.5");
+        assert_eq!(price(0.0, Locale::EN), "$0");
+    }
+
+    #[test]
+    fn subscriptzeros() {
+        assert_eq!(price(0.000_012_34, Locale::EN), "$0.0₄1234");
+        assert_eq!(price(0.000_000_012_34, Locale::EN), "$0.0₇1234");
+        assert_eq!(price(1.234e-15, Locale::EN), "$0.0₁₄1234");
+        assert_eq!(price(0.000_000_012_345_678, Locale::EN), "$0.0₇1235");
+    }
+
+    #[test]
+    fn roundingcarry() {
+        assert_eq!(price(0.999_96, Locale::EN), "$1.00");
+        assert_eq!(price(0.099_999, Locale::EN), "$0.1");
+        assert_eq!(price(999.999, Locale::EN), "$1,000.00");
+    }
+
+    #[test]
+    fn negativeprices() {
+        assert_eq!(price(-0.5, Locale::EN), "-$0.5");
+        assert_eq!(price(-1234.5, Locale::EN), "-$1,234.50");
+        assert_eq!(price(-0.000_000_012_34, Locale::EN), "-$0.0₇1234");
+    }
+
+    #[test]
+    fn notfinite() {
+        assert_eq!(price(f64::NAN, Locale::EN), "$N/A");
+        assert_eq!(price(f64::INFINITY, Locale::EN), "$N/A");
+        assert_eq!(price(f64::NEG_INFINITY, Locale::EN), "$N/A");
+        assert_eq!(percentage(f64::NAN, Locale::EN), "N/A%");
+        assert_eq!(percentage(f64::INFINITY, Locale::EN), "N/A%");
+        assert_eq!(compact(f64::NAN, Locale::EN), "N/A");
+        assert_eq!(compact(f64::NEG_INFINITY, Locale::EN), "N/A");
+    }
+
+    #[test]
+    fn pricestrings() {
+        assert_eq!(pricestr("0.00000001234", Locale::EN), "$0.0₇1234");
+        assert_eq!(pricestr(" 42 ", Locale::EN), "$42.00");
+        assert_eq!(pricestr("unknown", Locale::EN), "$unknown");
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(percentage(12.345, Locale::EN), "+12.35%");
+        assert_eq!(percentage(-0.5, Locale::EN), "-0.50%");
+        assert_eq!(percentage(0.0, Locale::EN), "+0.00%");
+        assert_eq!(percentage(-0.001, Locale::EN), "+0.00%");
+        assert_eq!(percentage(1234.5, Locale::EN), "+1,234.50%");
+    }
+
+    #[test]
+    fn compactboundaries() {
+        assert_eq!(compact(950.0, Locale::EN), "950");
+        assert_eq!(compact(999.0, Locale::EN), "999");
+        assert_eq!(compact(999.996, Locale::EN), "1K");
+        assert_eq!(compact(1_000.0, Locale::EN), "1K");
+        assert_eq!(compact(1_500.0, Locale::EN), "1.5K");
+        assert_eq!(compact(999_999.0, Locale::EN), "1M");
+        assert_eq!(compact(1_234_567.0, Locale::EN), "1.23M");
+        assert_eq!(compact(999_999_999.0, Locale::EN), "1B");
+        assert_eq!(compact(2_500_000_000.0, Locale::EN), "2.5B");
+        assert_eq!(compact(999_999_999_999.0, Locale::EN), "1T");
+        assert_eq!(compact(1_000_000_000_000_000.0, Locale::EN), "1,000T");
+    }
+
+    #[test]
+    fn compactnegativesandusd() {
+        assert_eq!(compact(-1_234_567.0, Locale::EN), "-1.23M");
+        assert_eq!(compactusd(1_234_567.0, Locale::EN), "$1.23M");
+        assert_eq!(compactusd(-1_500.0, Locale::EN), "-$1.5K");
+        assert_eq!(compactusd(0.0, Locale::EN), "$0");
+    }
+
+    #[test]
+    fn localeseparators() {
+        let value = 1_234_567.891;
+        assert_eq!(thousands(value, 2, Locale::EN), "1,234,567.89");
+        assert_eq!(thousands(value, 2, Locale::EU), "1.234.567,89");
+        assert_eq!(thousands(value, 2, Locale::CH), "1'234'567.89");
+        assert_eq!(
+            thousands(value, 2, Locale::FR),
+            "1\u{202f}234\u{202f}567,89"
+        );
+    }
+
+    #[test]
+    fn localesinprices() {
+        assert_eq!(price(0.000_000_012_34, Locale::EU), "$0,0₇1234");
+        assert_eq!(price(0.4213, Locale::FR), "$0,4213");
+        assert_eq!(price(67_120.45, Locale::CH), "$67'120.45");
+        assert_eq!(percentage(-12.5, Locale::EU), "-12,50%");
+        assert_eq!(compact(1_230_000.0, Locale::EU), "1,23M");
+        assert_eq!(compact(1_500.0, Locale::FR), "1,5K");
+        assert_eq!(compact(2_000_000.0, Locale::CH), "2M");
+    }
+
+    #[test]
+    fn thousandsnegatives() {
+        assert_eq!(thousands(-1234.5, 2, Locale::EN), "-1,234.50");
+        assert_eq!(thousands(-0.001, 2, Locale::EN), "0.00");
+        assert_eq!(thousands(123.0, 0, Locale::EN), "123");
+    }
+
+    #[test]
+    fn localenames() {
+        assert_eq!(Locale::default(), Locale::EN);
+        assert_eq!(Locale::from_name(" DE "), Some(Locale::EU));
+        assert_eq!(Locale::from_name("ch"), Some(Locale::CH));
+        assert_eq!(Locale::from_name("fr"), Some(Locale::FR));
+        assert_eq!(Locale::from_name("xx"), None);
+    }
+}
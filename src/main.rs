@@ -17,8 +17,18 @@ use poise::serenity_prelude::CacheHttp;
 use serenity::builder::CreateCommand;
 
 mod commands;
+mod database;
+mod dexscreener;
+mod events;
+mod formatting;
+mod migrations;
+mod risk;
 #[cfg(feature = "database")]
 mod settings;
+mod tasks;
+mod tokenindex;
+mod tokenlist;
+mod tokenstore;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
@@ -30,10 +40,7 @@ pub struct Data {}
 #[cfg(feature = "database")]
 use crate::settings::dbstructs::GuildCommands;
 #[cfg(feature = "database")]
-use ::{
-    once_cell::sync::Lazy, surrealdb::engine::any, surrealdb::engine::remote::ws::Wss,
-    surrealdb::Surreal,
-};
+use ::{once_cell::sync::Lazy, surrealdb::engine::any, surrealdb::Surreal};
 
 #[cfg(feature = "database")]
 static DB: Lazy<Surreal<any::Any>> = Lazy::new(Surreal::init);
@@ -64,6 +71,14 @@ async fn on_ready(
             // We get the saved commands for each guild by fetching them from the database, and push them into a vec of GuildCommands
             #[cfg(feature = "database")]
             {
+                // Without the database it is unknown which commands a guild turned off, so keep the commands registered before
+                if !database::available() {
+                    println!(
+                        "The database is unavailable, the commands of {} are not registered again",
+                        guild.id
+                    );
+                    continue;
+                }
                 let guildspecificcommands: Option<GuildCommands> =
                     DB.select(("guildcommands", guild.id.to_string())).await?;
                 let commandstoturnon = match guildspecificcommands {
@@ -97,10 +112,12 @@ fn specificcommandfinder(
     'commandloop: for frameworkcommand in commandsinframework {
         let name = &frameworkcommand.identifying_name;
         if commandsindatabase.contains(name) {
-            guildspecificcommands.push(match frameworkcommand.create_as_slash_command() {
+            guildspecificcommands.push(match createcommand(frameworkcommand) {
                 Some(command) => command,
                 None => {
-                    println!("{name} could not be created as slash command");
+                    println!(
+                        "{name} could not be created as slash command or context menu command"
+                    );
                     continue 'commandloop;
                 }
             });
@@ -109,17 +126,30 @@ fn specificcommandfinder(
     guildspecificcommands
 }
 
+/// Create a command as slash command, or as context menu command if it is not a slash command.
+pub fn createcommand(
+    frameworkcommand: &poise::Command<Data, Box<dyn std::error::Error + Send + Sync>>,
+) -> Option<CreateCommand> {
+    frameworkcommand
+        .create_as_slash_command()
+        .or_else(|| frameworkcommand.create_as_context_menu_command())
+}
+
 #[tokio::main]
 async fn main() {
-    #[cfg(all(feature = "memdatabase", feature = "filedatabase"))]
-    compile_error!("Choose one of the features [memdatabase or filedatabase]. Can not determine which one to use when both are supplied.");
-
     #[cfg(feature = "database")]
-    createdatabase().await;
+    database::createdatabase().await;
+    #[cfg(not(feature = "database"))]
+    tokenlist::load();
 
     println!("Starting bot");
     // Set GUILDS to be an intent as we require it for having custom commands
-    let intents = serenity::GatewayIntents::GUILDS;
+    let mut intents = serenity::GatewayIntents::GUILDS;
+    // Reading messages is a privileged intent, so only request it when message detection is turned on
+    if events::messagedetectionenabled() {
+        intents |=
+            serenity::GatewayIntents::GUILD_MESSAGES | serenity::GatewayIntents::MESSAGE_CONTENT;
+    }
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
@@ -127,10 +157,18 @@ async fn main() {
                 #[cfg(feature = "database")]
                 settings::settings::settings(),
                 #[cfg(feature = "database")]
+                commands::movers::movers(),
+                #[cfg(feature = "database")]
+                commands::tokens::tokens(),
+                #[cfg(feature = "database")]
+                commands::backup::backup(),
                 commands::price::price(),
-                #[cfg(not(feature = "database"))]
-                commands::pricewithoutdb::price(),
+                commands::pricemessage::pricemessage(),
+                commands::trending::trending(),
             ],
+            event_handler: |ctx, event, framework, data| {
+                Box::pin(events::eventhandler(ctx, event, framework, data))
+            },
             ..Default::default()
         })
         .setup(
@@ -141,6 +179,24 @@ async fn main() {
                     let _ = on_ready(ctx, ready, framework).await;
                     println!("The bot is done getting ready");
 
+                    tokio::spawn(tokenindex::keepfresh());
+                    #[cfg(feature = "database")]
+                    tokio::spawn(database::keepconnected());
+                    #[cfg(not(feature = "database"))]
+                    tokio::spawn(tokenlist::watch());
+                    #[cfg(feature = "database")]
+                    tokio::spawn(tasks::ticker::ticker(ctx.clone()));
+                    #[cfg(feature = "database")]
+                    tokio::spawn(tasks::statchannels::statchannels(ctx.clone()));
+                    #[cfg(feature = "database")]
+                    tokio::spawn(tasks::digests::digests(ctx.clone()));
+                    #[cfg(feature = "database")]
+                    tokio::spawn(tasks::boostfeed::boostfeed(ctx.clone()));
+                    #[cfg(feature = "database")]
+                    tokio::spawn(tasks::newpairs::newpairs(ctx.clone()));
+                    #[cfg(feature = "database")]
+                    tokio::spawn(tasks::marketalerts::marketalerts(ctx.clone()));
+
                     Ok(Data {})
                 })
             },
@@ -166,46 +222,3 @@ async fn main() {
         println!("Client error: {why:?}");
     }
 }
-
-#[cfg(feature = "database")]
-async fn createdatabase() {
-    println!("Creating database");
-    connectdatabase().await;
-    match DB.use_ns("dexscreener").use_db("dexscreenerdb").await {
-        Ok(val) => val,
-        Err(dberror) => panic!("failed to use namespace or datebase: {dberror}"),
-    };
-}
-
-cfg_if::cfg_if! {
-    if #[cfg(feature = "memdatabase")] {
-        async fn connectdatabase() {         match DB.connect("mem://").await {
-            Ok(val) => val,
-            Err(dbconnecterror) => panic!("failed to connect to database: {dbconnecterror}"),
-        };
-        println!("Created an in memory database");
-    }
-    } else if #[cfg(feature = "filedatabase")] {
-        async fn connectdatabase() {         match DB.connect("file://dexscreener.db").await {
-            Ok(val) => val,
-            Err(dbconnecterror) => panic!("failed to connect to database: {dbconnecterror}"),
-        };
-        println!("Created a file database"); }
-    } else if #[cfg(feature = "database")]{
-
-        async fn connectdatabase() {
-            let remoteaddress = match std::env::var("SURREAL_BIND") {
-                Ok(val) => {if !val.starts_with(|x :char| x.is_ascii_digit()) {val} else {
-                    format!("ws://{val}")
-                }},
-                Err(_) => "ws://localhost:8000".to_string()
-            };
-            println!("Connecting to a database on address: {remoteaddress}");
-            match DB.connect(remoteaddress).await {
-            Ok(val) => val,
-            Err(dbconnecterror) => panic!("failed to connect to database: {dbconnecterror}"),
-        };
-        println!("Created a remote database");
-    }
-    }
-}
@@ -0,0 +1,154 @@
+//! Versioned changes to the tables of the database, applied in order when the bot connects.
+//!
+//! The version of the database is stored in `schemaversion:current`. Never change a migration that is released,
+//! add a new one with the next version instead.
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::{dexscreener, Error, DB};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+
+type MigrationFuture = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+/// What a migration does.
+/// - `Query`: `SurrealQL` that is run in one transaction together with raising the version
+/// - `Function`: a change that can not be written in `SurrealQL`, e.g. because it needs Dexscreener.
+///   The version is raised after the function finished, so when the bot stops in between the function runs again
+///   on the next connection. Functions therefore have to be safe to run more than once.
+enum Change {
+    Query(&'static str),
+    Function(fn() -> MigrationFuture),
+}
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    change: Change,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SchemaVersion {
+    version: u32,
+}
+
+/// All migrations, ordered by version.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Define the tables, their fields and indexes",
+        change: Change::Query(
+            "DEFINE TABLE Coins SCHEMALESS;
+            DEFINE FIELD name ON Coins TYPE string;
+            DEFINE FIELD address ON Coins TYPE string;
+            DEFINE FIELD global ON Coins TYPE bool;
+            DEFINE FIELD verified ON Coins TYPE bool DEFAULT false;
+            DEFINE INDEX coinsaddress ON Coins FIELDS address;
+            DEFINE INDEX coinsguild ON Coins FIELDS guildid;
+            DEFINE TABLE guildcommands SCHEMALESS;
+            DEFINE FIELD commands ON guildcommands TYPE array<string>;
+            DEFINE TABLE availablesettings SCHEMALESS;
+            DEFINE FIELD availablecommands ON availablesettings TYPE bool;
+            DEFINE FIELD owneravailablecommands ON availablesettings TYPE bool;
+            DEFINE FIELD tokenpricetracking ON availablesettings TYPE bool;
+            DEFINE FIELD globaltokens ON availablesettings TYPE bool;
+            DEFINE TABLE availableslashcommands SCHEMALESS;
+            DEFINE FIELD availableslashcommands ON availableslashcommands TYPE array<string>;
+            DEFINE TABLE autodetect SCHEMALESS;
+            DEFINE TABLE ticker SCHEMALESS;
+            DEFINE TABLE statchannels SCHEMALESS;
+            DEFINE INDEX statchannelsguild ON statchannels FIELDS guildid;
+            DEFINE TABLE digests SCHEMALESS;
+            DEFINE TABLE boostfeeds SCHEMALESS;
+            DEFINE TABLE seenboosts SCHEMALESS;
+            DEFINE TABLE pairfeeds SCHEMALESS;
+            DEFINE TABLE seenpairs SCHEMALESS;
+            DEFINE INDEX seenpairsseenat ON seenpairs FIELDS seenat;
+            DEFINE TABLE marketalerts SCHEMALESS;
+            DEFINE TABLE tokenrequests SCHEMALESS;",
+        ),
+    },
+    Migration {
+        version: 2,
+        description: "Store the chain of every token",
+        change: Change::Function(|| Box::pin(addcoinchains())),
+    },
+    Migration {
+        version: 3,
+        description: "Define the table of the permissions given to roles and members",
+        change: Change::Query("DEFINE TABLE guildpermissions SCHEMALESS;"),
+    },
+    Migration {
+        version: 4,
+        description: "Index when boosts were seen, so old ones can be forgotten",
+        change: Change::Query("DEFINE INDEX seenboostsseenat ON seenboosts FIELDS seenat;"),
+    },
+];
+
+/// Apply the migrations that are newer than the version of the database.
+/// A migration that fails stops the others, and is tried again on the next connection.
+pub async fn migrate() -> Result<(), Error> {
+    let current: Option<SchemaVersion> = DB.select(("schemaversion", "current")).await?;
+    let current = current.map_or(0, |x| x.version);
+    for migration in MIGRATIONS.iter().filter(|x| x.version > current) {
+        println!(
+            "Migrating the database to version {}: {}",
+            migration.version, migration.description
+        );
+        match &migration.change {
+            Change::Query(query) => {
+                DB.query(format!(
+                    "BEGIN TRANSACTION; {query} UPDATE schemaversion:current CONTENT {{ version: {} }}; COMMIT TRANSACTION;",
+                    migration.version
+                ))
+                .await?
+                .check()?;
+            }
+            Change::Function(function) => {
+                // Not in one transaction with the version, see `Change::Function`
+                function().await?;
+                let _: Option<SchemaVersion> = DB
+                    .update(("schemaversion", "current"))
+                    .content(SchemaVersion {
+                        version: migration.version,
+                    })
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Look up the chain of the tokens that were added before chains were stored.
+/// Tokens that are no longer on Dexscreener keep an unknown chain.
+/// Safe to run more than once, as only tokens without a chain are looked up and updated.
+/// This is best effort: when Dexscreener can not be reached the chains stay unknown,
+/// as an outage of Dexscreener should not keep the bot from using the database.
+async fn addcoinchains() -> Result<(), Error> {
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let coins: Vec<Coins> = coins.into_iter().filter(|x| x.chain.is_none()).collect();
+    if coins.is_empty() {
+        return Ok(());
+    }
+    let addresses: Vec<String> = coins.iter().map(|x| x.address.clone()).collect();
+    let pairs = match dexscreener::bestpairs(&addresses).await {
+        Ok(pairs) => pairs,
+        Err(fetcherror) => {
+            println!("Could not look up the chains of the tokens, they stay unknown: {fetcherror}");
+            return Ok(());
+        }
+    };
+    for coin in coins {
+        let Some(pair) = pairs.get(&coin.address.to_lowercase()) else {
+            continue;
+        };
+        let _: Option<Coins> = DB
+            .update(("Coins", coin.name.clone()))
+            .content(Coins {
+                chain: Some(pair.chain_id.clone()),
+                ..coin
+            })
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! Warning signs of scam tokens, based on the pair data that is already fetched from Dexscreener.
+//!
+//! Every warning sign adds points to the score of a pair:
+//! - Very low liquidity, or liquidity that is tiny compared to the FDV
+//! - A pair that was created less than a day ago
+//! - Almost no sells (a possible honeypot) or almost only sells
+//! - A symbol that is the same as a known token, but with a different address
+use crate::dexscreener::Pair;
+use crate::formatting::{self, Locale};
+use crate::tokenstore::{store, TokenStore};
+
+/// Liquidity in dollars below which a pair is very risky to trade.
+const VERYLOWLIQUIDITY: f64 = 10_000.0;
+/// Liquidity in dollars below which a pair is somewhat risky to trade.
+const LOWLIQUIDITY: f64 = 50_000.0;
+/// Percentage of the FDV that should at least be in liquidity.
+const MINLIQUIDITYFDV: f64 = 2.0;
+/// Buys and sells are only compared when there were at least this many transactions in 24 hours.
+const MINTXNS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// The risk score of a pair and the warning signs that were found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Risk {
+    pub score: u32,
+    pub reasons: Vec<String>,
+}
+
+impl Risk {
+    pub fn level(&self) -> RiskLevel {
+        match self.score {
+            0..=24 => RiskLevel::Low,
+            25..=49 => RiskLevel::Medium,
+            _ => RiskLevel::High,
+        }
+    }
+
+    /// The badge shown on price cards, e.g. `🔴 High risk`.
+    pub fn badge(&self) -> &'static str {
+        match self.level() {
+            RiskLevel::Low => "🟢 Low risk",
+            RiskLevel::Medium => "🟡 Medium risk",
+            RiskLevel::High => "🔴 High risk",
+        }
+    }
+
+    fn add(&mut self, points: u32, reason: String) {
+        self.score += points;
+        self.reasons.push(reason);
+    }
+}
+
+/// Score a pair on the warning signs of scam tokens.
+/// `knowntokens` are the names and addresses of trusted tokens, used to find tokens that copy their symbol.
+pub fn assess(pair: &Pair, knowntokens: &[(String, String)]) -> Risk {
+    let locale = Locale::from_env();
+    let mut risk = Risk {
+        score: 0,
+        reasons: vec![],
+    };
+
+    let liquidity = pair.liquidity.as_ref().and_then(|x| x.usd).unwrap_or(0.0);
+    if liquidity < VERYLOWLIQUIDITY {
+        risk.add(
+            30,
+            format!(
+                "Very low liquidity ({})",
+                formatting::compactusd(liquidity, locale)
+            ),
+        );
+    } else if liquidity < LOWLIQUIDITY {
+        risk.add(
+            10,
+            format!(
+                "Low liquidity ({})",
+                formatting::compactusd(liquidity, locale)
+            ),
+        );
+    }
+    if let Some(fdv) = pair.fdv.filter(|x| *x > 0.0) {
+        let percentage = liquidity / fdv * 100.0;
+        if percentage < MINLIQUIDITYFDV {
+            risk.add(
+                25,
+                format!(
+                    "Liquidity is only {}% of the FDV",
+                    formatting::thousands(percentage, 2, locale)
+                ),
+            );
+        }
+    }
+
+    if let Some(createdat) = pair.pair_created_at {
+        let agehours = (unixnow() - createdat / 1000) / 3600;
+        if agehours < 1 {
+            risk.add(30, "The pair was created less than an hour ago".to_string());
+        } else if agehours < 24 {
+            risk.add(20, format!("The pair was created {agehours} hours ago"));
+        }
+    }
+
+    if let Some(txns) = pair.txns.as_ref().and_then(|x| x.h24.as_ref()) {
+        let total = txns.buys + txns.sells;
+        if total >= MINTXNS {
+            #[allow(clippy::cast_precision_loss)]
+            let sellshare = txns.sells as f64 / total as f64;
+            if sellshare < 0.1 {
+                risk.add(
+                    35,
+                    format!(
+                        "Almost no sells ({} buys, {} sells), selling might be blocked",
+                        txns.buys, txns.sells
+                    ),
+                );
+            } else if sellshare > 0.8 {
+                risk.add(
+                    15,
+                    format!("Mostly sells ({} buys, {} sells)", txns.buys, txns.sells),
+                );
+            }
+        }
+    }
+
+    let symbol = pair.base_token.symbol.to_lowercase();
+    if let Some((name, _)) = knowntokens.iter().find(|(name, address)| {
+        name.to_lowercase() == symbol && !address.eq_ignore_ascii_case(&pair.base_token.address)
+    }) {
+        risk.add(
+            50,
+            format!("Uses the symbol of {name}, but is a different token"),
+        );
+    }
+    risk
+}
+
+/// The current Unix timestamp in seconds.
+fn unixnow() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |x| i64::try_from(x.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// The names and addresses of the trusted tokens: the global and verified tokens.
+pub async fn knowntokens() -> Vec<(String, String)> {
+    match store().list().await {
+        Ok(tokens) => tokens
+            .into_iter()
+            .filter(|token| token.global || token.verified)
+            .map(|token| (token.name, token.address))
+            .collect(),
+        Err(storeerror) => {
+            println!("Could not get the known tokens: {storeerror}");
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dexscreener::{BaseToken, BuysSells, Liquidity, Txns};
+
+    const ADDRESS: &str = "0x0b2c639c533813f4aa9d7837caf62653d097ff85";
+    const OTHERADDRESS: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    /// A pair without any warning signs: deep liquidity, a month old and as many buys as sells.
+    fn safepair() -> Pair {
+        Pair {
+            base_token: BaseToken {
+                address: ADDRESS.to_string(),
+                name: "Safe Token".to_string(),
+                symbol: "SAFE".to_string(),
+            },
+            liquidity: Some(Liquidity {
+                usd: Some(1_000_000.0),
+                ..Liquidity::default()
+            }),
+            fdv: Some(10_000_000.0),
+            pair_created_at: Some((unixnow() - 30 * 24 * 3600) * 1000),
+            txns: Some(Txns {
+                h24: Some(BuysSells {
+                    buys: 100,
+                    sells: 100,
+                }),
+                ..Txns::default()
+            }),
+            ..Pair::default()
+        }
+    }
+
+    fn withliquidity(liquidity: f64) -> Pair {
+        Pair {
+            liquidity: Some(Liquidity {
+                usd: Some(liquidity),
+                ..Liquidity::default()
+            }),
+            fdv: None,
+            ..safepair()
+        }
+    }
+
+    fn withtxns(buys: u64, sells: u64) -> Pair {
+        Pair {
+            txns: Some(Txns {
+                h24: Some(BuysSells { buys, sells }),
+                ..Txns::default()
+            }),
+            ..safepair()
+        }
+    }
+
+    fn withage(hours: i64) -> Pair {
+        Pair {
+            pair_created_at: Some((unixnow() - hours * 3600 - 60) * 1000),
+            ..safepair()
+        }
+    }
+
+    /// The score, level and reasons of a pair.
+    fn assessed(pair: &Pair, knowntokens: &[(String, String)]) -> (u32, RiskLevel, Vec<String>) {
+        let risk = assess(pair, knowntokens);
+        (risk.score, risk.level(), risk.reasons)
+    }
+
+    /// The score and level of a pair with exactly one reason, which starts with `prefix`.
+    fn onereason(pair: &Pair, prefix: &str) -> (u32, RiskLevel) {
+        let (score, level, reasons) = assessed(pair, &[]);
+        assert_eq!(reasons.len(), 1, "{reasons:?}");
+        assert!(reasons[0].starts_with(prefix), "{reasons:?}");
+        (score, level)
+    }
+
+    #[test]
+    fn safepairhasnoreasons() {
+        assert_eq!(assessed(&safepair(), &[]), (0, RiskLevel::Low, vec![]));
+    }
+
+    #[test]
+    fn liquidity() {
+        assert_eq!(
+            onereason(&withliquidity(5_000.0), "Very low liquidity"),
+            (30, RiskLevel::Medium)
+        );
+        assert_eq!(
+            onereason(&withliquidity(20_000.0), "Low liquidity"),
+            (10, RiskLevel::Low)
+        );
+        assert_eq!(assessed(&withliquidity(50_000.0), &[]).0, 0);
+    }
+
+    #[test]
+    fn liquiditycomparedtofdv() {
+        let pair = Pair {
+            fdv: Some(100_000_000.0),
+            ..safepair()
+        };
+        assert_eq!(
+            onereason(&pair, "Liquidity is only"),
+            (25, RiskLevel::Medium)
+        );
+    }
+
+    #[test]
+    fn newpair() {
+        assert_eq!(
+            onereason(&withage(0), "The pair was created less than an hour ago"),
+            (30, RiskLevel::Medium)
+        );
+        assert_eq!(
+            onereason(&withage(5), "The pair was created 5 hours ago"),
+            (20, RiskLevel::Low)
+        );
+        assert_eq!(assessed(&withage(24), &[]).0, 0);
+    }
+
+    #[test]
+    fn buysandsells() {
+        assert_eq!(
+            onereason(&withtxns(95, 5), "Almost no sells"),
+            (35, RiskLevel::Medium)
+        );
+        assert_eq!(
+            onereason(&withtxns(10, 90), "Mostly sells"),
+            (15, RiskLevel::Low)
+        );
+        // Too few transactions to say anything
+        assert_eq!(assessed(&withtxns(15, 0), &[]).0, 0);
+    }
+
+    #[test]
+    fn impersonation() {
+        let knowntokens = vec![("safe".to_string(), OTHERADDRESS.to_string())];
+        let (score, level, reasons) = assessed(&safepair(), &knowntokens);
+        assert_eq!((score, level), (50, RiskLevel::High));
+        assert_eq!(
+            reasons,
+            vec!["Uses the symbol of safe, but is a different token".to_string()]
+        );
+        // The known token itself, with another casing of its address
+        let knowntokens = vec![("SAFE".to_string(), ADDRESS.to_uppercase())];
+        assert_eq!(assessed(&safepair(), &knowntokens).0, 0);
+    }
+
+    #[test]
+    fn reasonsaddup() {
+        let pair = Pair {
+            pair_created_at: Some(unixnow() * 1000),
+            ..withliquidity(5_000.0)
+        };
+        let (score, level, reasons) = assessed(&pair, &[]);
+        assert_eq!((score, level, reasons.len()), (60, RiskLevel::High, 2));
+    }
+
+    #[test]
+    fn levelboundaries() {
+        let level = |score| {
+            Risk {
+                score,
+                reasons: vec![],
+            }
+            .level()
+        };
+        assert_eq!(level(24), RiskLevel::Low);
+        assert_eq!(level(25), RiskLevel::Medium);
+        assert_eq!(level(49), RiskLevel::Medium);
+        assert_eq!(level(50), RiskLevel::High);
+    }
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "database")]
+use crate::events::messagedetectionenabled;
+use crate::settings::dbstructs::AutoDetect;
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelType, ComponentInteractionDataKind, CreateActionRow, CreateButton,
+    CreateSelectMenu, CreateSelectMenuKind,
+};
+
+use super::commonfunctions::getguildid;
+
+/// Choose the channels in which the bot replies to contract addresses, or turn it on or off for the whole guild.
+pub async fn autodetectsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+
+    let dbautodetect: Option<AutoDetect> = DB.select(("autodetect", guildid.to_string())).await?;
+    let mut autodetect = match dbautodetect {
+        Some(autodetect) => autodetect,
+        None => AutoDetect {
+            guildid,
+            enabled: true,
+            channels: vec![],
+        },
+    };
+
+    let channelmenuid = format!("autodetectchannels{}", ctx.id());
+    let togglebuttonid = format!("autodetecttoggle{}", ctx.id());
+    let channelmenu = CreateSelectMenu::new(
+        channelmenuid,
+        CreateSelectMenuKind::Channel {
+            channel_types: Some(vec![ChannelType::Text, ChannelType::News]),
+            default_channels: Some(autodetect.channels.clone()),
+        },
+    )
+    .min_values(0)
+    .max_values(25)
+    .placeholder("No channels chosen");
+    let togglebutton = if autodetect.enabled {
+        CreateButton::new(togglebuttonid)
+            .label("Turn off in all channels")
+            .style(ButtonStyle::Danger)
+    } else {
+        CreateButton::new(togglebuttonid)
+            .label("Turn on in the chosen channels")
+            .style(ButtonStyle::Success)
+    };
+
+    let Some(interaction) = menu
+        .page(
+            format!(
+                "Select the channels in which the bot replies to contract addresses with their price.\nAutomatic replies are currently **{}** in {} channels.",
+                if autodetect.enabled { "on" } else { "off" },
+                autodetect.channels.len()
+            ),
+            vec![
+                CreateActionRow::SelectMenu(channelmenu),
+                CreateActionRow::Buttons(vec![togglebutton]),
+            ],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+
+    match &interaction.data.kind {
+        ComponentInteractionDataKind::ChannelSelect { values } => {
+            autodetect.channels.clone_from(values);
+        }
+        ComponentInteractionDataKind::Button => autodetect.enabled = !autodetect.enabled,
+        _ => return Err("unexpected interaction data kind".into()),
+    }
+
+    let _: Option<AutoDetect> = DB
+        .update(("autodetect", guildid.to_string()))
+        .content(&autodetect)
+        .await?;
+
+    let channels: Vec<String> = autodetect
+        .channels
+        .iter()
+        .map(|channelid| format!("<#{channelid}>"))
+        .collect();
+    let mut text = format!(
+        "Automatic replies are **{}** in: {}",
+        if autodetect.enabled { "on" } else { "off" },
+        if channels.is_empty() {
+            "no channels".to_string()
+        } else {
+            channels.join(", ")
+        }
+    );
+    if !messagedetectionenabled() {
+        text.push_str("\n\nThe bot can not read messages yet. Ask the bot owner to set `MESSAGE_DETECTION` to `true`.");
+    }
+    menu.finish(text).await?;
+
+    Ok(())
+}
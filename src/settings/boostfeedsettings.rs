@@ -0,0 +1,136 @@
+#![cfg(feature = "database")]
+use crate::settings::dbstructs::BoostFeed;
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelType, ComponentInteractionDataKind, CreateActionRow, CreateButton,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+
+use super::commonfunctions::getguildid;
+
+/// Chains that can be chosen for a feed, as named by Dexscreener.
+const CHAINS: [&str; 8] = [
+    "solana",
+    "ethereum",
+    "base",
+    "bsc",
+    "arbitrum",
+    "polygon",
+    "avalanche",
+    "sui",
+];
+
+/// Choose a channel in which newly boosted tokens are posted.
+//
+// 1. Choose the channel
+// 2. Choose the chain, or stop posting in that channel
+#[allow(clippy::too_many_lines)]
+pub async fn boostfeedsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+
+    let boostfeeds: Vec<BoostFeed> = DB.select("boostfeeds").await?;
+    let currentfeeds: Vec<String> = boostfeeds
+        .iter()
+        .filter(|boostfeed| boostfeed.guildid == guildid)
+        .map(|boostfeed| {
+            format!(
+                "<#{}>: {}",
+                boostfeed.channelid,
+                boostfeed.chain.as_deref().unwrap_or("all chains")
+            )
+        })
+        .collect();
+
+    // 1. Choose the channel
+    let channelmenuid = format!("boostfeedchannel{}", ctx.id());
+    let Some(interaction) = menu.page(format!(
+                    "Select the channel in which newly boosted tokens are posted.\nCurrent boost feeds:\n{}",
+                    if currentfeeds.is_empty() {
+                        "None".to_string()
+                    } else {
+                        currentfeeds.join("\n")
+                    }
+                ), vec![CreateActionRow::SelectMenu(CreateSelectMenu::new(
+                        channelmenuid.clone(),
+                        CreateSelectMenuKind::Channel {
+                            channel_types: Some(vec![ChannelType::Text, ChannelType::News]),
+                            default_channels: None,
+                        },
+                    )
+                    .max_values(1)
+                    .placeholder("No channel chosen"))]).await? else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::ChannelSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let Some(channelid) = values.first().copied() else {
+        return Ok(());
+    };
+
+    // 2. Choose the chain, or stop posting
+    let chainmenuid = format!("boostfeedchain{}", ctx.id());
+    let removebuttonid = format!("boostfeedremove{}", ctx.id());
+    let mut chainoptions = vec![CreateSelectMenuOption::new("All chains", "all")];
+    chainoptions.extend(
+        CHAINS
+            .iter()
+            .map(|chain| CreateSelectMenuOption::new(*chain, *chain)),
+    );
+    let Some(interaction) = menu
+        .page(
+            format!("Select the chain of the tokens posted in <#{channelid}>"),
+            vec![
+                CreateActionRow::SelectMenu(
+                    CreateSelectMenu::new(
+                        chainmenuid.clone(),
+                        CreateSelectMenuKind::String {
+                            options: chainoptions,
+                        },
+                    )
+                    .max_values(1)
+                    .placeholder("No chain chosen"),
+                ),
+                CreateActionRow::Buttons(vec![CreateButton::new(removebuttonid.clone())
+                    .label("Stop posting boosts in this channel")
+                    .style(ButtonStyle::Danger)]),
+            ],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let chain = match &interaction.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => match values.first() {
+            Some(chain) if chain != "all" => Some(chain.clone()),
+            _ => None,
+        },
+        ComponentInteractionDataKind::Button => {
+            let _: Option<BoostFeed> = DB.delete(("boostfeeds", channelid.to_string())).await?;
+            menu.finish(format!(
+                "<#{channelid}> does not get boosted tokens anymore"
+            ))
+            .await?;
+            return Ok(());
+        }
+        _ => return Err("unexpected interaction data kind".into()),
+    };
+
+    let _: Option<BoostFeed> = DB
+        .update(("boostfeeds", channelid.to_string()))
+        .content(BoostFeed {
+            guildid,
+            channelid,
+            chain: chain.clone(),
+        })
+        .await?;
+
+    menu.finish(format!(
+        "Newly boosted tokens on {} will be posted in <#{channelid}>",
+        chain.as_deref().unwrap_or("all chains")
+    ))
+    .await?;
+    Ok(())
+}
@@ -1,101 +1,38 @@
 #![cfg(feature = "database")]
 use crate::{
     settings::dbstructs::{AvailableSlashcommands, GuildCommands},
-    Context, Error, DB,
+    Error, DB,
 };
-use poise::serenity_prelude::{
-    CreateEmbed, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
-};
-use poise::CreateReply;
 use serenity::all::GuildId;
 
 use super::commonfunctions::ownercheck;
+use super::owneravailablecommands::guildselectmenu;
+use super::picker::{Picked, Picker, PickerOption};
+use super::settingsmenu::SettingsMenu;
 
 /// Check if the owner invokes this command as he can turn on slash commands across guilds
 /// After this go to guildstobechanged
-pub async fn ownercheckcommandselection(ctx: Context<'_>) -> Result<(), Error> {
+pub async fn ownercheckcommandselection(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
     // If you are not bot owner continue like normal in your current guild
     let ownercheck = ownercheck(ctx, None).await?;
     if !ownercheck {
-        let guildid = match ctx.guild_id() {
-            Some(guildid) => guildid,
-            None => {
-                ctx.send(
-                    CreateReply::new()
-                        .content("It looks you are not in a server")
-                        .ephemeral(true),
-                )
-                .await?;
-                return Ok(());
-            }
+        let Some(guildid) = ctx.guild_id() else {
+            menu.finish("It looks you are not in a server").await?;
+            return Ok(());
         };
         // Invoke command selection if you are not the owner
-        commandselection(ctx, guildid).await?;
+        commandselection(menu, guildid).await?;
         // Return the function as completed
         return Ok(());
     }
     // From here on it continues only for owners
-    let guilds = ctx.http().get_guilds(None, None).await?;
-    let mut vecofguildmenu = vec![];
-    for guild in guilds {
-        vecofguildmenu.push(CreateSelectMenuOption::new(
-            guild.name,
-            guild.id.to_string(),
-        ));
-    }
-
-    let customid = format!("guildmenu{}", ctx.id());
-    let message = ctx
-        .channel_id()
-        .send_message(
-            ctx,
-            CreateMessage::new()
-                .content("Please click the guild you want to change")
-                .select_menu(
-                    CreateSelectMenu::new(
-                        customid.clone(),
-                        CreateSelectMenuKind::String {
-                            options: vecofguildmenu,
-                        },
-                    )
-                    .max_values(1)
-                    .placeholder("No guild chosen"),
-                ),
-        )
-        .await?;
-    let interaction = match message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 3))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![customid])
-        .await
-    {
-        Some(x) => x,
-        None => {
-            message.delete(&ctx).await?;
-            ctx.send(
-                CreateReply::new()
-                    .content("Command settings timed ou:/")
-                    .ephemeral(true),
-            )
-            .await?;
-            return Ok(());
-        }
-    };
-
-    message.delete(ctx).await?;
-
-    let interactionvalue = match &interaction.data.kind {
-        poise::serenity_prelude::ComponentInteractionDataKind::StringSelect { values } => {
-            &values[0]
-        }
-        _ => panic!("unexpected interaction data kind"),
+    let Some(guildtobechanged) = guildselectmenu(menu).await? else {
+        return Ok(());
     };
 
-    let guildtobechanged = GuildId::new(interactionvalue.parse::<u64>()?);
-
     // Invoke commandselection if you are the owner
-    commandselection(ctx, guildtobechanged).await?;
+    commandselection(menu, guildtobechanged).await?;
 
     Ok(())
 }
@@ -120,13 +57,13 @@ pub async fn getguildavailablecommands(guildid: GuildId) -> Result<Vec<String>,
 3. Go over all commands in the bot and check if guild may use them. If so, put them as available in a selectmenu.
 */
 #[allow(clippy::too_many_lines)]
-async fn commandselection(ctx: Context<'_>, guildid: GuildId) -> Result<(), Error> {
+async fn commandselection(menu: &mut SettingsMenu<'_>, guildid: GuildId) -> Result<(), Error> {
+    let ctx = menu.ctx;
     // Get the commands that are already active in the guild, these are preselected.
     let predefinedslashcommands: Vec<String> = match guildid.get_commands(ctx.http()).await {
         Ok(getcommands) => getcommands.iter().map(|com| com.name.clone()).collect(),
         Err(_) => vec![],
     };
-    let customid = format!("CommandMenu{}", ctx.id());
 
     // Check which commands are available in a guild to be turned on
     let availablecommands = getguildavailablecommands(guildid).await?;
@@ -136,78 +73,44 @@ async fn commandselection(ctx: Context<'_>, guildid: GuildId) -> Result<(), Erro
     // Loop over all available commands in framework.
     // If they are also available in this specific guild, make them a selectmenuoption
     // Settings are specifically excluded here as they should always be global, and not guild specific
-    let mut selectmenuvec = vec![];
+    let mut options = vec![];
+    let mut selected = vec![];
     for command in commandsinframework {
         if command.identifying_name != "settings"
             && availablecommands.contains(&command.identifying_name)
         {
-            let selectmenuoption = CreateSelectMenuOption::new(
+            options.push(PickerOption::new(
                 command
                     .description
                     .clone()
                     .unwrap_or(command.identifying_name.clone()),
                 command.identifying_name.clone(),
-            )
-            .default_selection(predefinedslashcommands.contains(&command.name));
-            selectmenuvec.push(selectmenuoption);
-        }
-    }
-    // If the number is bigger than u8, set 25 as that is the Discord limit. Also check if the u8 if bigger than 25.
-    let veclen = match u8::try_from(selectmenuvec.len()) {
-        Ok(number) => {
-            if number >= 25 {
-                25
-            } else {
-                number
+            ));
+            // Context menu commands are registered under their context menu name
+            if predefinedslashcommands.contains(&command.name)
+                || command
+                    .context_menu_name
+                    .as_ref()
+                    .is_some_and(|name| predefinedslashcommands.contains(name))
+            {
+                selected.push(command.identifying_name.clone());
             }
         }
-        Err(_) => 25,
-    };
-    let message = ctx
-        .channel_id()
-        .send_message(
-            ctx,
-            CreateMessage::new()
-                .content("Please select the commands that you want to be available")
-                .select_menu(
-                    CreateSelectMenu::new(
-                        customid.clone(),
-                        CreateSelectMenuKind::String {
-                            options: selectmenuvec.clone(),
-                        },
-                    )
-                    .placeholder("No Setting selected")
-                    .min_values(0)
-                    .max_values(veclen),
-                ),
-        )
-        .await?;
-
-    let interaction = match message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 5))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![customid])
-        .await
-    {
-        Some(x) => x,
-        None => {
-            message.delete(&ctx).await?;
-            ctx.send(
-                CreateReply::new()
-                    .content("Command settings timed ou:/")
-                    .ephemeral(true),
-            )
-            .await?;
-            return Ok(());
-        }
-    };
-
-    message.delete(ctx).await?;
-
-    let interactionvalue = match &interaction.data.kind {
-        poise::serenity_prelude::ComponentInteractionDataKind::StringSelect { values } => values,
-        _ => panic!("unexpected interaction data kind"),
+    }
+    let Some(Picked::Values(interactionvalue)) = Picker::new(
+        format!(
+            "Commands active in {}: {}\nPlease select the commands that you want to be available",
+            guildid.name(ctx).unwrap_or("Unknown".to_string()),
+            predefinedslashcommands.join(", ")
+        ),
+        options,
+    )
+    .multiple(selected)
+    .placeholder("No Setting selected")
+    .pick(menu)
+    .await?
+    else {
+        return Ok(());
     };
 
     // Vec of commands that will be registered later on
@@ -220,27 +123,17 @@ async fn commandselection(ctx: Context<'_>, guildid: GuildId) -> Result<(), Erro
         if interactionvalue.contains(name) || name == "settings" {
             dbcommands.push(name.clone());
 
-            guildspecificcommands.push(match frameworkcommand.create_as_slash_command() {
+            guildspecificcommands.push(match crate::createcommand(frameworkcommand) {
                 Some(command) => command,
                 None => {
-                    println!("{name:#?} could not be created as slash command");
+                    println!(
+                        "{name:#?} could not be created as slash command or context menu command"
+                    );
                     continue 'commandloop;
                 }
             });
         }
     }
-    let guildid = match ctx.guild_id() {
-        Some(guildid) => guildid,
-        None => {
-            ctx.send(
-                CreateReply::new()
-                    .content("It looks you are not in a server")
-                    .ephemeral(true),
-            )
-            .await?;
-            return Ok(());
-        }
-    };
     // Store the commands in the database / update the stored commands in the database
     let _: Option<GuildCommands> = DB
         .update(("guildcommands", guildid.to_string()))
@@ -250,31 +143,20 @@ async fn commandselection(ctx: Context<'_>, guildid: GuildId) -> Result<(), Erro
         })
         .await?;
 
-    let submittedcommands = match guildid.set_commands(ctx, guildspecificcommands).await {
-        Ok(val) => val,
-        Err(_) => {
-            ctx.send(
-                    CreateReply::new()
-                        .content("Something went wrong while registering commands, please try again.\nIf it still doens't work, please contact us!")
-                        .ephemeral(true),
-                )
-                .await?;
-            return Ok(());
-        }
+    let Ok(submittedcommands) = guildid.set_commands(ctx, guildspecificcommands).await else {
+        menu.finish("Something went wrong while registering commands, please try again.\nIf it still doens't work, please contact us!").await?;
+        return Ok(());
     };
 
-    // Create a vec of embed fields that contains each registered command in current guild
-    let mut commandsforembed: Vec<(String, String, bool)> = vec![];
+    let mut registered = vec![];
     for command in submittedcommands {
-        commandsforembed.push((command.name, command.description, false));
+        registered.push(format!("**{}**: {}", command.name, command.description));
     }
-
-    // Send this embed
-    let embed = CreateEmbed::new()
-        .fields(commandsforembed)
-        .description("The following commands are available in your guild:");
-    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
-        .await?;
+    menu.finish(format!(
+        "The following commands are available in your guild:\n{}",
+        registered.join("\n")
+    ))
+    .await?;
 
     Ok(())
 }
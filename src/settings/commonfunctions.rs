@@ -1,7 +1,9 @@
 use poise::CreateReply;
 use serenity::all::GuildId;
 
-use crate::{Context, Error};
+use crate::commands::price::Coins;
+use crate::settings::dbstructs::{AvailableSettings, Capability, GuildPermissions};
+use crate::{tokenindex, Context, Error, DB};
 /// Checks if a user is part of the botowner's team, returning a bool.
 /// If text is supplied, this send as an ephemeral message to the user with the supplied text.
 pub async fn ownercheck(ctx: Context<'_>, text: Option<&str>) -> Result<bool, Error> {
@@ -22,11 +24,7 @@ pub async fn ownercheck(ctx: Context<'_>, text: Option<&str>) -> Result<bool, Er
 /// Checks if a user is admin of this guild, returning a bool.
 /// If text is supplied, this send as an ephemeral message to the user with the supplied text.
 pub async fn admincheck(ctx: Context<'_>, text: Option<&str>) -> Result<bool, Error> {
-    let admincheck = ctx
-        .author_member()
-        .await
-        .and_then(|m| m.permissions)
-        .is_some_and(poise::serenity_prelude::Permissions::administrator);
+    let admincheck = isadmin(ctx).await;
 
     if !admincheck {
         match ctx.send(
@@ -42,6 +40,147 @@ pub async fn admincheck(ctx: Context<'_>, text: Option<&str>) -> Result<bool, Er
     Ok(admincheck)
 }
 
+/// Checks if the author has the administrator permission in this guild, without telling them.
+pub async fn isadmin(ctx: Context<'_>) -> bool {
+    ctx.author_member()
+        .await
+        .and_then(|m| m.permissions)
+        .is_some_and(poise::serenity_prelude::Permissions::administrator)
+}
+
+/// Get the capabilities of the author in the guild of the command.
+/// Bot owners and administrators have all capabilities, others have the ones given to them or one of their roles.
+pub async fn capabilities(ctx: Context<'_>) -> Result<Vec<Capability>, Error> {
+    let Some(guildid) = ctx.guild_id() else {
+        return Ok(vec![]);
+    };
+    if ctx.framework().options.owners.contains(&ctx.author().id) {
+        return Ok(Capability::ALL.to_vec());
+    }
+    if isadmin(ctx).await {
+        return Ok(Capability::ALL.to_vec());
+    }
+    let Some(member) = ctx.author_member().await else {
+        return Ok(vec![]);
+    };
+    let guildpermissions: Option<GuildPermissions> =
+        DB.select(("guildpermissions", guildid.to_string())).await?;
+    Ok(guildpermissions
+        .map(|x| x.grants)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|grant| {
+            grant.users.contains(&member.user.id)
+                || grant.roles.iter().any(|role| member.roles.contains(role))
+        })
+        .map(|grant| grant.capability)
+        .collect())
+}
+
+/// Check if the author has a capability, and tell them if they do not.
+pub async fn capabilitycheck(ctx: Context<'_>, capability: Capability) -> Result<bool, Error> {
+    let hascapability = capabilities(ctx).await?.contains(&capability);
+    if !hascapability {
+        ctx.send(
+            CreateReply::new()
+                .content(format!(
+                    "You need the *{}* permission for this, ask an administrator of this server to give it to you.",
+                    capability.name()
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+    }
+    Ok(hascapability)
+}
+
+/// Command check: the author has at least one capability, so there is a setting they can change.
+pub async fn settingscheck(ctx: Context<'_>) -> Result<bool, Error> {
+    let cansettings = !capabilities(ctx).await?.is_empty();
+    if !cansettings {
+        ctx.send(
+            CreateReply::new()
+                .content("Sorry, you are not allowed to use this command.\nOnly administrators, bot owners and members who got a permission from an administrator are.")
+                .ephemeral(true),
+        )
+        .await?;
+    }
+    Ok(cansettings)
+}
+
+/// Command check: the author can manage tokens.
+pub async fn managetokenscheck(ctx: Context<'_>) -> Result<bool, Error> {
+    capabilitycheck(ctx, Capability::Tokens).await
+}
+
+/// Command check: the author can manage alerts.
+pub async fn alertscheck(ctx: Context<'_>) -> Result<bool, Error> {
+    capabilitycheck(ctx, Capability::Alerts).await
+}
+
+/// Command check: the author can manage schedules.
+pub async fn schedulescheck(ctx: Context<'_>) -> Result<bool, Error> {
+    capabilitycheck(ctx, Capability::Schedules).await
+}
+
+/// Command check: the author can manage commands.
+pub async fn commandscheck(ctx: Context<'_>) -> Result<bool, Error> {
+    capabilitycheck(ctx, Capability::Commands).await
+}
+
+/// Command check: the database is available, otherwise ask the author to try again later.
+pub async fn databasecheck(ctx: Context<'_>) -> Result<bool, Error> {
+    let available = crate::database::available();
+    if !available {
+        ctx.send(
+            CreateReply::new()
+                .content("The database is unavailable right now, try again in a few minutes.")
+                .ephemeral(true),
+        )
+        .await?;
+    }
+    Ok(available)
+}
+
+/// Command check: the author is an administrator of the guild or a bot owner.
+pub async fn administratorcheck(ctx: Context<'_>) -> Result<bool, Error> {
+    Ok(ownercheck(ctx, None).await?
+        || admincheck(ctx, Some("Only administrators can do this")).await?)
+}
+
+/// Command check: the author is a bot owner.
+pub async fn botownercheck(ctx: Context<'_>) -> Result<bool, Error> {
+    ownercheck(ctx, Some("Only the bot owner can do this")).await
+}
+
+/// Get the settings the bot owner allows in a guild, or the defaults if they were never changed.
+/// Bot owners can always change everything.
+pub async fn availablesettings(
+    ctx: Context<'_>,
+    guildid: GuildId,
+) -> Result<AvailableSettings, Error> {
+    if ctx.framework().options.owners.contains(&ctx.author().id) {
+        return Ok(AvailableSettings {
+            availablecommands: true,
+            owneravailablecommands: true,
+            tokenpricetracking: true,
+            globaltokens: true,
+        });
+    }
+    let dbcommandpermissions: Option<AvailableSettings> = DB
+        .select(("availablesettings", guildid.to_string()))
+        .await?;
+    Ok(match dbcommandpermissions {
+        Some(permissions) => permissions,
+        None => AvailableSettings {
+            availablecommands: true,
+            owneravailablecommands: false,
+            tokenpricetracking: true,
+            globaltokens: false,
+        },
+    })
+}
+
 /// Get the guildid. Return an error if it can not be found and send an ephemeral message to the suer about it.
 pub async fn getguildid(ctx: Context<'_>) -> Result<GuildId, Error> {
     match ctx.guild_id() {
@@ -57,3 +196,34 @@ pub async fn getguildid(ctx: Context<'_>) -> Result<GuildId, Error> {
         }
     }
 }
+
+/// Get the coins that can be used in a guild, these are the coins of that guild and the global coins.
+pub async fn guildcoins(guildid: GuildId) -> Result<Vec<Coins>, Error> {
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    Ok(coins
+        .into_iter()
+        .filter(|coin| coin.global || coin.guildid == guildid)
+        .collect())
+}
+
+/// Find a global or verified token that has the same symbol or name as a token, but a different address.
+/// Global and verified tokens are compared on the name they are stored under, and on their symbol and name on Dexscreener.
+pub fn findlookalike<'a>(
+    coins: &'a [Coins],
+    symbol: &str,
+    name: &str,
+    address: &str,
+) -> Option<&'a Coins> {
+    let issame = |candidate: &str| {
+        !candidate.trim().is_empty()
+            && (candidate.eq_ignore_ascii_case(symbol) || candidate.eq_ignore_ascii_case(name))
+    };
+    coins.iter().find(|coin| {
+        (coin.global || coin.verified)
+            && !coin.address.eq_ignore_ascii_case(address)
+            && (issame(&coin.name)
+                || tokenindex::details(&coin.address).is_some_and(
+                    |(indexedsymbol, indexedname)| issame(&indexedsymbol) || issame(&indexedname),
+                ))
+    })
+}
@@ -1,5 +1,5 @@
 #![cfg(feature = "database")]
-use poise::serenity_prelude::GuildId;
+use poise::serenity_prelude::{ChannelId, GuildId, RoleId, UserId};
 use serde::{Deserialize, Serialize};
 
 /// Which settings someone can change in the bot.
@@ -34,3 +34,181 @@ pub struct GuildCommands {
     pub guildid: GuildId,
     pub commands: Vec<String>,
 }
+
+/// The channels of a guild in which the bot replies to contract addresses with a price card.
+/// - `enabled`: Turn detection on or off for the whole guild without losing the chosen channels
+/// - `channels`: The channels that opted in
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoDetect {
+    pub guildid: GuildId,
+    pub enabled: bool,
+    pub channels: Vec<ChannelId>,
+}
+
+/// The token shown in the nickname of the bot in a guild.
+/// The nickname is only changed while `enabled` is true.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ticker {
+    pub guildid: GuildId,
+    pub name: String,
+    pub address: String,
+    pub enabled: bool,
+}
+
+/// A channel that is renamed to show the price or market cap of a token, e.g. `BTC: $67,120`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatChannel {
+    pub guildid: GuildId,
+    pub channelid: ChannelId,
+    pub name: String,
+    pub address: String,
+    pub display: StatDisplay,
+}
+
+/// What a stat channel shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatDisplay {
+    Price,
+    MarketCap,
+}
+
+/// A digest of the tokens of a guild that is posted in a channel on a schedule.
+/// - `timezone`: IANA name of the timezone used for daily schedules, e.g. `Europe/Amsterdam`
+/// - `nextpost`: Unix timestamp in seconds of when the digest is posted next
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Digest {
+    pub guildid: GuildId,
+    pub channelid: ChannelId,
+    pub schedule: DigestSchedule,
+    pub timezone: String,
+    pub nextpost: i64,
+}
+
+/// When a digest is posted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DigestSchedule {
+    /// Every day at the same time
+    Daily { hour: u32, minute: u32 },
+    /// Every few hours
+    Interval { hours: u32 },
+}
+
+/// A channel in which newly boosted tokens are posted, optionally only for one chain.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoostFeed {
+    pub guildid: GuildId,
+    pub channelid: ChannelId,
+    pub chain: Option<String>,
+}
+
+/// A boosted token that was already posted in the feeds, so it is not posted twice.
+/// `seenat` is a unix timestamp, boosts seen before it was stored count as seen long ago.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeenBoost {
+    pub chainid: String,
+    pub tokenaddress: String,
+    #[serde(default)]
+    pub seenat: i64,
+}
+
+/// A channel in which newly created pairs on a chain are posted.
+/// - `dex`: only post pairs on this DEX, e.g. `raydium`
+/// - `minliquidity`: minimum liquidity in dollars
+/// - `maxage`: maximum age of a pair in minutes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PairFeed {
+    pub guildid: GuildId,
+    pub channelid: ChannelId,
+    pub chain: String,
+    pub dex: Option<String>,
+    pub minliquidity: f64,
+    pub maxage: i64,
+}
+
+/// A pair that was already posted in a new pair feed, so it is not posted twice.
+/// - `seenat`: Unix timestamp in seconds of when the pair was posted or skipped
+/// - `channelid`: the feed it was posted in, `None` for pairs that are skipped in all feeds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeenPair {
+    pub chainid: String,
+    pub pairaddress: String,
+    pub seenat: i64,
+    #[serde(default)]
+    pub channelid: Option<ChannelId>,
+}
+
+/// Warnings about sudden volume spikes and liquidity drops of the tokens of a guild, posted in one channel.
+/// - `volumefactor`: warn when the 5 minute volume is this many times the average of the window
+/// - `liquiditydrop`: warn when the liquidity dropped this many percent below the highest value of the window
+/// - `window`: length of the window in minutes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarketAlerts {
+    pub guildid: GuildId,
+    pub channelid: ChannelId,
+    pub volumefactor: f64,
+    pub liquiditydrop: f64,
+    pub window: i64,
+}
+
+/// A request of a guild without the `globaltokens` permission to add a token to all guilds, reviewed by the bot owner.
+/// - `channelid`: channel in which the request was made, the result of the review is posted there
+/// - `requestedat`: Unix timestamp in seconds of when the request was made
+/// - `chain`: the chain on Dexscreener, unknown for requests made before it was stored
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenRequest {
+    pub name: String,
+    pub address: String,
+    pub guildid: GuildId,
+    pub channelid: ChannelId,
+    pub requester: UserId,
+    pub requestedat: i64,
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+/// What guild administrators can allow roles and users without the administrator permission to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Capability {
+    /// Add tokens and import or export them
+    Tokens,
+    /// Automatic replies, boost and pair feeds and market alerts
+    Alerts,
+    /// Ticker, stat channels and digests
+    Schedules,
+    /// Turn commands on and off
+    Commands,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::Tokens,
+        Capability::Alerts,
+        Capability::Schedules,
+        Capability::Commands,
+    ];
+
+    /// The name used in menus and in the ids of select menu options.
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::Tokens => "Manage tokens",
+            Capability::Alerts => "Manage alerts",
+            Capability::Schedules => "Manage schedules",
+            Capability::Commands => "Manage commands",
+        }
+    }
+}
+
+/// The roles and users that got a capability in a guild.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityGrant {
+    pub capability: Capability,
+    pub roles: Vec<RoleId>,
+    pub users: Vec<UserId>,
+}
+
+/// The capabilities given to roles and users of a guild, administrators and bot owners always have all of them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuildPermissions {
+    pub guildid: GuildId,
+    pub grants: Vec<CapabilityGrant>,
+}
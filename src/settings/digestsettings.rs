@@ -0,0 +1,179 @@
+#![cfg(feature = "database")]
+use crate::settings::dbstructs::Digest;
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::tasks::digests::{describeschedule, nextpost, parseschedule, parsetimezone};
+use crate::{Error, DB};
+use chrono::Utc;
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelType, ComponentInteractionDataKind, CreateActionRow, CreateButton,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use poise::Modal;
+
+use super::commonfunctions::getguildid;
+
+#[derive(Debug, Modal, Clone)]
+#[name = "Schedule a price digest"]
+struct DigestModal {
+    #[name = "Time (09:00 = daily, 4h = every 4 hours)"]
+    #[placeholder = "09:00"]
+    #[max_length = 5]
+    schedule: String,
+    #[name = "Timezone for daily digests"]
+    #[placeholder = "Europe/Amsterdam"]
+    timezone: Option<String>,
+}
+
+/// Add or remove digests of the tokens of this guild that are posted on a schedule.
+//
+// 1. Show the current digests, with the option to remove one or add a new one
+// 2. When adding, ask for the schedule and timezone in a modal
+// 3. Choose the channel the digest is posted in
+#[allow(clippy::too_many_lines)]
+pub async fn digestsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+    let digests: Vec<Digest> = DB.select("digests").await?;
+    let digests: Vec<Digest> = digests
+        .into_iter()
+        .filter(|digest| digest.guildid == guildid)
+        .collect();
+
+    let removemenuid = format!("digestremove{}", ctx.id());
+    let addbuttonid = format!("digestadd{}", ctx.id());
+    let mut components = vec![];
+    if !digests.is_empty() {
+        let mut options = vec![];
+        for digest in digests.iter().take(25) {
+            // Select menus can not show channel mentions, so use the name of the channel
+            let channelname = match digest.channelid.name(ctx).await {
+                Ok(name) => format!("#{name}"),
+                Err(_) => digest.channelid.to_string(),
+            };
+            options.push(CreateSelectMenuOption::new(
+                format!(
+                    "{channelname}: {}",
+                    describeschedule(digest.schedule, &digest.timezone)
+                ),
+                digest.channelid.to_string(),
+            ));
+        }
+        components.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                removemenuid.clone(),
+                CreateSelectMenuKind::String { options },
+            )
+            .max_values(1)
+            .placeholder("Remove a digest"),
+        ));
+    }
+    components.push(CreateActionRow::Buttons(vec![CreateButton::new(
+        addbuttonid.clone(),
+    )
+    .label("Add a digest")
+    .style(ButtonStyle::Primary)]));
+
+    let currentdigests: Vec<String> = digests
+        .iter()
+        .map(|digest| {
+            format!(
+                "<#{}>: {}",
+                digest.channelid,
+                describeschedule(digest.schedule, &digest.timezone)
+            )
+        })
+        .collect();
+    let Some(interaction) = menu.page(format!(
+                    "Digests post the price, change and volume of the tokens of this guild on a schedule.\nCurrent digests:\n{}",
+                    if currentdigests.is_empty() {
+                        "None".to_string()
+                    } else {
+                        currentdigests.join("\n")
+                    }
+                ), components).await? else {
+        return Ok(());
+    };
+
+    if let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind {
+        let Some(channelid) = values.first() else {
+            return Ok(());
+        };
+        let _: Option<Digest> = DB.delete(("digests", channelid.clone())).await?;
+        menu.finish(format!("The digest in <#{channelid}> is removed"))
+            .await?;
+        return Ok(());
+    }
+
+    // 2. Ask for the schedule
+    let Some(modalresponse) = menu.modal::<DigestModal>(interaction).await? else {
+        return Ok(());
+    };
+    let Some(schedule) = parseschedule(&modalresponse.schedule) else {
+        menu.finish(format!(
+                    "`{}` is not a valid schedule. Use a time like `09:00` for daily digests, or `4h` for every 4 hours.",
+                    modalresponse.schedule
+                )).await?;
+        return Ok(());
+    };
+    let timezonename = modalresponse
+        .timezone
+        .filter(|x| !x.trim().is_empty())
+        .unwrap_or("UTC".to_string());
+    let Some(timezone) = parsetimezone(&timezonename) else {
+        menu.finish(format!(
+                    "`{timezonename}` is not a known timezone. Use a name like `Europe/Amsterdam` or `America/New_York`."
+                )).await?;
+        return Ok(());
+    };
+    let Some(nextpost) = nextpost(schedule, Utc::now(), timezone) else {
+        return Err("Could not calculate when the digest is posted".into());
+    };
+
+    // 3. Choose the channel
+    let channelmenuid = format!("digestchannel{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            "Select the channel the digest is posted in",
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    channelmenuid.clone(),
+                    CreateSelectMenuKind::Channel {
+                        channel_types: Some(vec![ChannelType::Text, ChannelType::News]),
+                        default_channels: None,
+                    },
+                )
+                .max_values(1)
+                .placeholder("No channel chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::ChannelSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let Some(channelid) = values.first().copied() else {
+        return Ok(());
+    };
+
+    let _: Option<Digest> = DB
+        .update(("digests", channelid.to_string()))
+        .content(Digest {
+            guildid,
+            channelid,
+            schedule,
+            timezone: timezone.name().to_string(),
+            nextpost: nextpost.timestamp(),
+        })
+        .await?;
+
+    menu.finish(format!(
+        "A digest is posted in <#{channelid}> {}. The first one is posted <t:{}:R>.",
+        describeschedule(schedule, timezone.name()),
+        nextpost.timestamp()
+    ))
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "database")]
+use crate::settings::dbstructs::MarketAlerts;
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::tasks::marketalerts::MINWINDOW;
+use crate::{Error, DB};
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelType, ComponentInteractionDataKind, CreateActionRow, CreateButton,
+    CreateSelectMenu, CreateSelectMenuKind,
+};
+use poise::Modal;
+
+use super::commonfunctions::getguildid;
+
+#[derive(Debug, Modal, Clone)]
+#[name = "Volume and liquidity alerts"]
+struct MarketAlertModal {
+    #[name = "Warn when the volume is this many times higher"]
+    #[placeholder = "5"]
+    volumefactor: Option<String>,
+    #[name = "Warn when the liquidity drops this many %"]
+    #[placeholder = "30"]
+    liquiditydrop: Option<String>,
+    // Windows shorter than `MINWINDOW` can never hold enough samples
+    #[name = "Window in minutes, from 8 to 1440"]
+    #[placeholder = "60"]
+    window: Option<String>,
+}
+
+/// Warn in a channel when the volume of a token of this guild spikes or its liquidity drops.
+//
+// 1. Show the current settings, with the option to change or turn off the alerts
+// 2. Ask for the thresholds in a modal
+// 3. Choose the channel the warnings are posted in
+#[allow(clippy::too_many_lines)]
+pub async fn marketalertsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+    let current: Option<MarketAlerts> = DB.select(("marketalerts", guildid.to_string())).await?;
+
+    let setupbuttonid = format!("marketalertsetup{}", ctx.id());
+    let offbuttonid = format!("marketalertoff{}", ctx.id());
+    let mut buttons = vec![CreateButton::new(setupbuttonid.clone())
+        .label("Set up alerts")
+        .style(ButtonStyle::Primary)];
+    if current.is_some() {
+        buttons.push(
+            CreateButton::new(offbuttonid.clone())
+                .label("Turn off alerts")
+                .style(ButtonStyle::Danger),
+        );
+    }
+    let Some(interaction) = menu.page(format!(
+                    "Alerts warn when the volume of a token spikes or its liquidity is pulled.\nCurrently: {}",
+                    match &current {
+                        Some(config) => describealerts(config),
+                        None => "off".to_string(),
+                    }
+                ), vec![CreateActionRow::Buttons(buttons)]).await? else {
+        return Ok(());
+    };
+
+    if interaction.data.custom_id != setupbuttonid {
+        let _: Option<MarketAlerts> = DB.delete(("marketalerts", guildid.to_string())).await?;
+        menu.finish("Volume and liquidity alerts are turned off")
+            .await?;
+        return Ok(());
+    }
+
+    // 2. Ask for the thresholds
+    let Some(modalresponse) = menu.modal::<MarketAlertModal>(interaction).await? else {
+        return Ok(());
+    };
+    let number = |value: Option<String>, default: f64| match value
+        .map(|x| x.trim().trim_end_matches(['%', 'x']).to_string())
+        .filter(|x| !x.is_empty())
+    {
+        Some(val) => val.parse::<f64>().ok().filter(|x| x.is_finite()),
+        None => Some(default),
+    };
+    let volumefactor = number(modalresponse.volumefactor, 5.0).filter(|x| *x > 1.0);
+    let liquiditydrop =
+        number(modalresponse.liquiditydrop, 30.0).filter(|x| *x > 0.0 && *x < 100.0);
+    #[allow(clippy::cast_precision_loss)]
+    let window =
+        number(modalresponse.window, 60.0).filter(|x| (MINWINDOW as f64..=1440.0).contains(x));
+    let (Some(volumefactor), Some(liquiditydrop), Some(window)) =
+        (volumefactor, liquiditydrop, window)
+    else {
+        menu.finish(format!("Use a volume factor above 1, a liquidity drop between 0 and 100%, and a window between {MINWINDOW} and 1440 minutes, shorter windows can not hold enough checks")).await?;
+        return Ok(());
+    };
+
+    // 3. Choose the channel
+    let channelmenuid = format!("marketalertchannel{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            "Select the channel the alerts are posted in",
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    channelmenuid.clone(),
+                    CreateSelectMenuKind::Channel {
+                        channel_types: Some(vec![ChannelType::Text, ChannelType::News]),
+                        default_channels: current.as_ref().map(|x| vec![x.channelid]),
+                    },
+                )
+                .max_values(1)
+                .placeholder("No channel chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::ChannelSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let Some(channelid) = values.first().copied() else {
+        return Ok(());
+    };
+
+    #[allow(clippy::cast_possible_truncation)]
+    let config = MarketAlerts {
+        guildid,
+        channelid,
+        volumefactor,
+        liquiditydrop,
+        window: window.round() as i64,
+    };
+    let _: Option<MarketAlerts> = DB
+        .update(("marketalerts", guildid.to_string()))
+        .content(&config)
+        .await?;
+
+    menu.finish(format!(
+        "Alerts are posted in <#{channelid}>: {}",
+        describealerts(&config)
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Describe the thresholds, e.g. `volume above 5x or liquidity down 30% within 60 minutes`.
+fn describealerts(config: &MarketAlerts) -> String {
+    format!(
+        "volume above {}x or liquidity down {}% within {} minutes",
+        config.volumefactor, config.liquiditydrop, config.window
+    )
+}
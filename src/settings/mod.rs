@@ -1,6 +1,18 @@
+pub mod autodetectsettings;
+pub mod boostfeedsettings;
 pub mod commandselection;
 pub mod commonfunctions;
 pub mod dbstructs;
+pub mod digestsettings;
+pub mod marketalertsettings;
+pub mod newpairsettings;
 pub mod owneravailablecommands;
 pub mod permissionsettings;
+pub mod picker;
+pub mod rolepermissions;
 pub mod settings;
+pub mod settingsmenu;
+pub mod statchannelsettings;
+pub mod tickersettings;
+pub mod tokenrequests;
+pub mod verifytokens;
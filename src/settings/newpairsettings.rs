@@ -0,0 +1,208 @@
+#![cfg(feature = "database")]
+use crate::formatting::{self, Locale};
+use crate::settings::dbstructs::PairFeed;
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelType, ComponentInteractionDataKind, CreateActionRow, CreateButton,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use poise::Modal;
+
+use super::commonfunctions::getguildid;
+
+/// The maximum age of a pair that can be chosen, in minutes.
+const MAXAGE: i64 = 60 * 24;
+
+#[derive(Debug, Modal, Clone)]
+#[name = "Post new pairs"]
+struct PairFeedModal {
+    #[name = "Chain"]
+    #[placeholder = "solana"]
+    chain: String,
+    #[name = "DEX, all DEXes if empty"]
+    #[placeholder = "raydium"]
+    dex: Option<String>,
+    #[name = "Minimum liquidity in dollars"]
+    #[placeholder = "10000"]
+    minliquidity: Option<String>,
+    #[name = "Maximum age of a pair in minutes"]
+    #[placeholder = "60"]
+    maxage: Option<String>,
+}
+
+/// Add or remove channels in which newly created pairs are posted.
+//
+// 1. Show the current feeds, with the option to remove one or add a new one
+// 2. When adding, ask for the chain, DEX, minimum liquidity and maximum age in a modal
+// 3. Choose the channel the pairs are posted in
+#[allow(clippy::too_many_lines)]
+pub async fn newpairsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+    let pairfeeds: Vec<PairFeed> = DB.select("pairfeeds").await?;
+    let pairfeeds: Vec<PairFeed> = pairfeeds
+        .into_iter()
+        .filter(|pairfeed| pairfeed.guildid == guildid)
+        .collect();
+
+    let removemenuid = format!("pairfeedremove{}", ctx.id());
+    let addbuttonid = format!("pairfeedadd{}", ctx.id());
+    let mut components = vec![];
+    if !pairfeeds.is_empty() {
+        let mut options = vec![];
+        for pairfeed in pairfeeds.iter().take(25) {
+            // Select menus can not show channel mentions, so use the name of the channel
+            let channelname = match pairfeed.channelid.name(ctx).await {
+                Ok(name) => format!("#{name}"),
+                Err(_) => pairfeed.channelid.to_string(),
+            };
+            options.push(CreateSelectMenuOption::new(
+                format!("{channelname}: {}", describefeed(pairfeed)),
+                pairfeed.channelid.to_string(),
+            ));
+        }
+        components.push(CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                removemenuid.clone(),
+                CreateSelectMenuKind::String { options },
+            )
+            .max_values(1)
+            .placeholder("Remove a feed"),
+        ));
+    }
+    components.push(CreateActionRow::Buttons(vec![CreateButton::new(
+        addbuttonid.clone(),
+    )
+    .label("Add a feed")
+    .style(ButtonStyle::Primary)]));
+
+    let currentfeeds: Vec<String> = pairfeeds
+        .iter()
+        .map(|pairfeed| format!("<#{}>: {}", pairfeed.channelid, describefeed(pairfeed)))
+        .collect();
+    let Some(interaction) = menu
+        .page(
+            format!(
+                "New pair feeds post pairs that were just created on a chain.\nCurrent feeds:\n{}",
+                if currentfeeds.is_empty() {
+                    "None".to_string()
+                } else {
+                    currentfeeds.join("\n")
+                }
+            ),
+            components,
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+
+    if let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind {
+        let Some(channelid) = values.first() else {
+            return Ok(());
+        };
+        let _: Option<PairFeed> = DB.delete(("pairfeeds", channelid.clone())).await?;
+        menu.finish(format!(
+            "New pairs are not posted in <#{channelid}> anymore"
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    // 2. Ask for the filters
+    let Some(modalresponse) = menu.modal::<PairFeedModal>(interaction).await? else {
+        return Ok(());
+    };
+    let nonempty = |value: Option<String>| {
+        value
+            .map(|x| x.trim().to_lowercase())
+            .filter(|x| !x.is_empty())
+    };
+    let chain = modalresponse.chain.trim().to_lowercase();
+    let dex = nonempty(modalresponse.dex);
+    let minliquidity = match nonempty(modalresponse.minliquidity) {
+        Some(val) => match val.replace([',', '$'], "").parse::<f64>() {
+            Ok(val) if val.is_finite() && val >= 0.0 => val,
+            _ => {
+                menu.finish(format!("`{val}` is not a valid amount of liquidity"))
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => 0.0,
+    };
+    let maxage = match nonempty(modalresponse.maxage) {
+        Some(val) => match val.parse::<i64>() {
+            Ok(val) if (1..=MAXAGE).contains(&val) => val,
+            _ => {
+                menu.finish(format!(
+                    "`{val}` is not a valid age, use a number of minutes between 1 and {MAXAGE}"
+                ))
+                .await?;
+                return Ok(());
+            }
+        },
+        None => 60,
+    };
+
+    // 3. Choose the channel
+    let channelmenuid = format!("pairfeedchannel{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            "Select the channel the new pairs are posted in",
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    channelmenuid.clone(),
+                    CreateSelectMenuKind::Channel {
+                        channel_types: Some(vec![ChannelType::Text, ChannelType::News]),
+                        default_channels: None,
+                    },
+                )
+                .max_values(1)
+                .placeholder("No channel chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::ChannelSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let Some(channelid) = values.first().copied() else {
+        return Ok(());
+    };
+
+    let pairfeed = PairFeed {
+        guildid,
+        channelid,
+        chain,
+        dex,
+        minliquidity,
+        maxage,
+    };
+    let _: Option<PairFeed> = DB
+        .update(("pairfeeds", channelid.to_string()))
+        .content(&pairfeed)
+        .await?;
+
+    menu.finish(format!(
+        "New pairs are posted in <#{channelid}>: {}",
+        describefeed(&pairfeed)
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Describe the filters of a feed, e.g. `raydium on solana, at least $10K liquidity, at most 60 minutes old`.
+fn describefeed(pairfeed: &PairFeed) -> String {
+    format!(
+        "{} on {}, at least {} liquidity, at most {} minutes old",
+        pairfeed.dex.as_deref().unwrap_or("all DEXes"),
+        pairfeed.chain,
+        formatting::compactusd(pairfeed.minliquidity, Locale::from_env()),
+        pairfeed.maxage
+    )
+}
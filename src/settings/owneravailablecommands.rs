@@ -1,22 +1,22 @@
 #![cfg(feature = "database")]
-use crate::{Context, Error, DB};
-use poise::serenity_prelude::{
-    CreateEmbed, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
-};
-use poise::CreateReply;
+use crate::settings::picker::{Picked, Picker, PickerOption};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
 use serde::{Deserialize, Serialize};
-use serenity::all::GuildId;
+use serenity::all::{GuildId, GuildPagination};
 
 use super::commonfunctions::ownercheck;
 
 /// Check if the owner invokes this command as he can make slash commands available for guilds
-pub async fn ownercheckavailablecommands(ctx: Context<'_>) -> Result<(), Error> {
-    if !ownercheck(ctx, Some("You are not the bot owner!")).await? {
+pub async fn ownercheckavailablecommands(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    if !ownercheck(menu.ctx, Some("You are not the bot owner!")).await? {
         return Ok(());
     }
-    let guildtobechanged = guildselectmenu(ctx).await?;
+    let Some(guildtobechanged) = guildselectmenu(menu).await? else {
+        return Ok(());
+    };
 
-    availablecommandselection(ctx, guildtobechanged).await?;
+    availablecommandselection(menu, guildtobechanged).await?;
 
     Ok(())
 }
@@ -28,7 +28,11 @@ pub struct AvailableSlashcommands {
 }
 
 /// Set commands to be available in a guild to turn on or off. Only available for owners.
-async fn availablecommandselection(ctx: Context<'_>, guildid: GuildId) -> Result<(), Error> {
+async fn availablecommandselection(
+    menu: &mut SettingsMenu<'_>,
+    guildid: GuildId,
+) -> Result<(), Error> {
+    let ctx = menu.ctx;
     // Check which commands are available in a guild to be turned on
     let availablecommands =
         crate::settings::commandselection::getguildavailablecommands(guildid).await?;
@@ -36,73 +40,28 @@ async fn availablecommandselection(ctx: Context<'_>, guildid: GuildId) -> Result
     // Get all the commands available in the bot.
     let commandsinframework = &ctx.framework().options().commands;
 
-    let mut selectmenuvec = vec![];
+    let mut options = vec![];
     for command in commandsinframework {
         let name = &command.identifying_name;
-        selectmenuvec.push(
-            CreateSelectMenuOption::new(name, name)
-                .default_selection(availablecommands.contains(name)),
-        );
+        options.push(PickerOption::new(name, name));
     }
-    let customid = format!("availablecommandsmenu{}", ctx.id());
-
-    let selectmenuveclength = if selectmenuvec.len() <= 25 {
-        u8::try_from(selectmenuvec.len()).unwrap_or(25)
-    } else {
-        ctx.send(
-            CreateReply::new()
-                .content("Only showing the first 25 commands")
-                .ephemeral(true),
-        )
-        .await?;
-        25
-    };
 
-    let message = ctx
-        .channel_id()
-        .send_message(
-            ctx,
-            CreateMessage::new()
-                .content("Select the commands that should be available")
-                .select_menu(
-                    CreateSelectMenu::new(
-                        customid.clone(),
-                        CreateSelectMenuKind::String {
-                            options: selectmenuvec.clone(),
-                        },
-                    )
-                    .min_values(0)
-                    .max_values(selectmenuveclength)
-                    .placeholder("No commands chosen"),
-                ),
-        )
-        .await?;
-    let Some(interaction) = message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 3))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![customid])
-        .await
+    let guildname = guildid.name(ctx).unwrap_or("Unknown".to_string());
+    let Some(Picked::Values(interactionvalue)) = Picker::new(
+        format!(
+            "Commands available in {guildname}: {}\nSelect the commands that should be available",
+            availablecommands.join(", ")
+        ),
+        options,
+    )
+    .multiple(availablecommands)
+    .placeholder("No commands chosen")
+    .pick(menu)
+    .await?
     else {
-        message.delete(&ctx).await?;
-        ctx.send(
-            CreateReply::new()
-                .content("Command settings timed ou:/")
-                .ephemeral(true),
-        )
-        .await?;
         return Ok(());
     };
 
-    message.delete(ctx).await?;
-
-    let poise::serenity_prelude::ComponentInteractionDataKind::StringSelect {
-        values: interactionvalue,
-    } = &interaction.data.kind
-    else {
-        panic!("unexpected interaction data kind")
-    };
-
     let mut commandstobesetavailable = vec![];
     for commands in commandsinframework {
         if interactionvalue.contains(&commands.identifying_name) {
@@ -120,90 +79,66 @@ async fn availablecommandselection(ctx: Context<'_>, guildid: GuildId) -> Result
         })
         .await?;
 
-    // Create a vec of embed fields that contains each registered command in current guild
-    let mut commandsforembed: Vec<(String, String, bool)> = vec![];
-
-    if let Some(dbavailableslashcommands) = optionavailableslashcommands {
-        for command in dbavailableslashcommands.availableslashcommands {
-            commandsforembed.push((command, String::new(), false));
-        }
-    }
-    let embed = CreateEmbed::new()
-        .fields(commandsforembed)
-        .description(format!(
-            "The following commands are available in {:#?}:",
-            guildid.name(ctx).unwrap_or("Unknown".to_string())
-        ));
-    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
-        .await?;
+    let commands = match optionavailableslashcommands {
+        Some(dbavailableslashcommands) => dbavailableslashcommands.availableslashcommands,
+        None => vec![],
+    };
+    menu.finish(format!(
+        "The following commands are available in {guildname}:\n{}",
+        commands.join("\n")
+    ))
+    .await?;
 
     Ok(())
 }
 
-/// Create a select menu including all guilds.
-/// The user can choose one guild of which the guildid is returned.
-pub async fn guildselectmenu(ctx: Context<'_>) -> Result<GuildId, Error> {
-    let guilds = match ctx.http().get_guilds(None, None).await {
-        Ok(guildsfetch) => guildsfetch,
-        Err(errorguildsfetch) => {
-            return Err(
-                format!("The bot was not able to get the guilds: {errorguildsfetch}").into(),
-            )
+/// Get every guild the bot is in, Discord returns at most 200 at a time.
+async fn allguilds(ctx: crate::Context<'_>) -> Result<Vec<PickerOption>, Error> {
+    let mut options = vec![];
+    let mut after = None;
+    loop {
+        let guilds = match ctx
+            .http()
+            .get_guilds(after.map(GuildPagination::After), Some(200))
+            .await
+        {
+            Ok(guildsfetch) => guildsfetch,
+            Err(errorguildsfetch) => {
+                return Err(
+                    format!("The bot was not able to get the guilds: {errorguildsfetch}").into(),
+                )
+            }
+        };
+        let Some(last) = guilds.last() else {
+            return Ok(options);
+        };
+        after = Some(last.id);
+        let complete = guilds.len() < 200;
+        for guild in guilds {
+            options.push(PickerOption::new(guild.name, guild.id.to_string()));
+        }
+        if complete {
+            return Ok(options);
         }
-    };
-    let mut vecofguildmenu = vec![];
-    for guild in guilds {
-        vecofguildmenu.push(CreateSelectMenuOption::new(
-            guild.name,
-            guild.id.to_string(),
-        ));
     }
+}
 
-    let customid = format!("guildmenu{}", ctx.id());
-    let message = ctx
-        .channel_id()
-        .send_message(
-            ctx,
-            CreateMessage::new()
-                .content("Please click the guild you want to change")
-                .select_menu(
-                    CreateSelectMenu::new(
-                        customid.clone(),
-                        CreateSelectMenuKind::String {
-                            options: vecofguildmenu,
-                        },
-                    )
-                    .max_values(1)
-                    .placeholder("No guild chosen"),
-                ),
-        )
-        .await?;
-    let Some(interaction) = message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 3))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![customid])
-        .await
+/// Let the owner pick one of the guilds of the bot.
+/// Returns the guildid, or `None` when the menu was left.
+pub async fn guildselectmenu(menu: &mut SettingsMenu<'_>) -> Result<Option<GuildId>, Error> {
+    let options = allguilds(menu.ctx).await?;
+    let Some(Picked::Values(values)) =
+        Picker::new("Please click the guild you want to change", options)
+            .placeholder("No guild chosen")
+            .pick(menu)
+            .await?
     else {
-        message.delete(&ctx).await?;
-        ctx.send(
-            CreateReply::new()
-                .content("Command settings timed out:/")
-                .ephemeral(true),
-        )
-        .await?;
-        return Err("Command settings timed out:/".into());
+        return Ok(None);
     };
-
-    message.delete(ctx).await?;
-
-    let interactionvalue = match &interaction.data.kind {
-        poise::serenity_prelude::ComponentInteractionDataKind::StringSelect { values } => {
-            &values[0]
-        }
-        _ => panic!("unexpected interaction data kind"),
+    let Some(interactionvalue) = values.first() else {
+        return Ok(None);
     };
 
     let guildtobechanged = GuildId::new(interactionvalue.parse::<u64>()?);
-    Ok(guildtobechanged)
+    Ok(Some(guildtobechanged))
 }
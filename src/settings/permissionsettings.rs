@@ -1,17 +1,17 @@
 #![cfg(feature = "database")]
 use crate::settings::dbstructs::AvailableSettings;
-use crate::{Context, Error, DB};
-use poise::serenity_prelude::CreateMessage;
-use poise::serenity_prelude::CreateSelectMenu;
-use poise::serenity_prelude::CreateSelectMenuKind;
-use poise::serenity_prelude::CreateSelectMenuOption;
-use poise::CreateReply;
+use crate::settings::picker::{Picked, Picker, PickerOption};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
 
-use super::commonfunctions::getguildid;
+use super::owneravailablecommands::guildselectmenu;
 
-/// Setting the permissions a guild has
-pub async fn permissionsettings(ctx: Context<'_>) -> Result<(), Error> {
-    let guildid = getguildid(ctx).await?;
+/// Setting the permissions a guild has, the owner first picks the guild
+pub async fn permissionsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let Some(guildid) = guildselectmenu(menu).await? else {
+        return Ok(());
+    };
 
     let dbcommandpermissions: Option<AvailableSettings> = DB
         .select(("availablesettings", guildid.to_string()))
@@ -26,60 +26,38 @@ pub async fn permissionsettings(ctx: Context<'_>) -> Result<(), Error> {
             globaltokens: false,
         },
     };
-    let selectmenuvec = vec![
-        CreateSelectMenuOption::new(
+    let options = vec![
+        PickerOption::new(
             "Change which tokens are registered in a guild",
             "availablecommands",
-        )
-        .default_selection(commandpermissions.availablecommands),
-        CreateSelectMenuOption::new("Add tokens to be tracked in /price", "tokenpricetracking")
-            .default_selection(commandpermissions.tokenpricetracking),
-        CreateSelectMenuOption::new("Be able to set tokens to be global", "globaltokens")
-            .default_selection(commandpermissions.globaltokens),
+        ),
+        PickerOption::new("Add tokens to be tracked in /price", "tokenpricetracking"),
+        PickerOption::new("Be able to set tokens to be global", "globaltokens"),
     ];
+    let selected = [
+        ("availablecommands", commandpermissions.availablecommands),
+        ("tokenpricetracking", commandpermissions.tokenpricetracking),
+        ("globaltokens", commandpermissions.globaltokens),
+    ]
+    .into_iter()
+    .filter(|(_, given)| *given)
+    .map(|(name, _)| name.to_string());
 
-    let customid = format!("permissionsettings{}", ctx.id());
-    let message = ctx
-        .channel_id()
-        .send_message(
-            ctx,
-            CreateMessage::new()
-                .content("Please click the permissions you want to give this guild")
-                .select_menu(
-                    CreateSelectMenu::new(
-                        &customid,
-                        CreateSelectMenuKind::String {
-                            options: selectmenuvec,
-                        },
-                    )
-                    .max_values(3)
-                    .placeholder("No Setting chosen"),
-                ),
-        )
-        .await?;
-    let interaction = match message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 3))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![customid])
-        .await
-    {
-        Some(x) => x,
-        None => {
-            message.reply(&ctx, "Timed out").await?;
-            return Ok(());
-        }
-    };
-
-    let interactionvalue = match &interaction.data.kind {
-        poise::serenity_prelude::ComponentInteractionDataKind::StringSelect { values } => {
-            values.clone()
-        }
-        _ => panic!("unexpected interaction data kind"),
+    let Some(Picked::Values(interactionvalue)) = Picker::new(
+        format!(
+            "Please click the permissions you want to give {}",
+            guildid.name(ctx.cache()).unwrap_or(guildid.to_string())
+        ),
+        options,
+    )
+    .multiple(selected)
+    .placeholder("No Setting chosen")
+    .pick(menu)
+    .await?
+    else {
+        return Ok(());
     };
 
-    message.delete(ctx).await?;
-
     let selectedpermissions = AvailableSettings {
         availablecommands: interactionvalue.contains(&"availablecommands".to_string()),
         owneravailablecommands: false,
@@ -92,15 +70,11 @@ pub async fn permissionsettings(ctx: Context<'_>) -> Result<(), Error> {
         .content(selectedpermissions)
         .await?;
 
-    ctx.send(
-        CreateReply::default()
-            .content(format!(
-                "Set for {} the following permissions:\n{:#?}",
-                guildid.name(ctx.cache()).unwrap_or(guildid.to_string()),
-                dbresult
-            ))
-            .ephemeral(true),
-    )
+    menu.finish(format!(
+        "Set for {} the following permissions:\n{:#?}",
+        guildid.name(ctx.cache()).unwrap_or(guildid.to_string()),
+        dbresult
+    ))
     .await?;
 
     Ok(())
@@ -0,0 +1,267 @@
+#![cfg(feature = "database")]
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::Error;
+use poise::serenity_prelude::{
+    ButtonStyle, ComponentInteractionDataKind, CreateActionRow, CreateButton, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption,
+};
+use poise::Modal;
+use std::collections::HashSet;
+
+/// Discord allows at most 25 options in a select menu
+const PAGESIZE: usize = 25;
+/// Discord allows at most 100 characters in the label, value and description of an option
+const MAXOPTIONLENGTH: usize = 100;
+
+#[derive(Debug, Modal)]
+#[name = "Search"]
+struct PickerSearch {
+    #[name = "Only show options that contain"]
+    #[placeholder = "Leave empty to show everything"]
+    filter: Option<String>,
+}
+
+pub struct PickerOption {
+    pub label: String,
+    pub value: String,
+    pub description: Option<String>,
+}
+
+impl PickerOption {
+    pub fn new(label: impl Into<String>, value: impl Into<String>) -> Self {
+        PickerOption {
+            label: truncate(label.into()),
+            value: truncate(value.into()),
+            description: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(truncate(description.into()));
+        self
+    }
+
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        self.label.to_lowercase().contains(&filter)
+            || self.value.to_lowercase().contains(&filter)
+            || self
+                .description
+                .as_ref()
+                .is_some_and(|description| description.to_lowercase().contains(&filter))
+    }
+}
+
+fn truncate(text: String) -> String {
+    if text.chars().count() > MAXOPTIONLENGTH {
+        text.chars().take(MAXOPTIONLENGTH).collect()
+    } else {
+        text
+    }
+}
+
+/// What the author did with a picker.
+pub enum Picked {
+    /// The chosen values, one for a single choice
+    Values(Vec<String>),
+    /// The custom id of one of the extra buttons
+    Button(String),
+}
+
+/// A select menu for any number of options, shown 25 at a time with buttons to go to the previous or next page and to search.
+/// With `multiple` the selection is kept while paging, and a done button confirms it.
+pub struct Picker {
+    content: String,
+    options: Vec<PickerOption>,
+    placeholder: String,
+    multiple: bool,
+    selected: HashSet<String>,
+    buttons: Vec<CreateButton>,
+}
+
+impl Picker {
+    pub fn new(content: impl Into<String>, options: Vec<PickerOption>) -> Self {
+        Picker {
+            content: content.into(),
+            options,
+            placeholder: "Nothing chosen".to_string(),
+            multiple: false,
+            selected: HashSet::new(),
+            buttons: vec![],
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Let the author choose any number of options, starting with `selected`.
+    pub fn multiple(mut self, selected: impl IntoIterator<Item = String>) -> Self {
+        self.multiple = true;
+        self.selected = selected.into_iter().collect();
+        self
+    }
+
+    /// Show an extra button under the options, pressing it returns `Picked::Button` with its custom id.
+    pub fn button(mut self, button: CreateButton) -> Self {
+        self.buttons.push(button);
+        self
+    }
+
+    /// Show the picker in the menu until the author chooses.
+    /// Returns `None` when the author left the menu, like `SettingsMenu::page`.
+    /*
+    1. Filter the options and show the current page
+    2. Move between pages or change the filter with the buttons
+    3. Return the choice, the whole selection or the extra button
+    */
+    #[allow(clippy::too_many_lines)]
+    pub async fn pick(mut self, menu: &mut SettingsMenu<'_>) -> Result<Option<Picked>, Error> {
+        let ctx = menu.ctx;
+        let selectid = format!("pickerselect{}", ctx.id());
+        let previousid = format!("pickerprevious{}", ctx.id());
+        let nextid = format!("pickernext{}", ctx.id());
+        let searchid = format!("pickersearch{}", ctx.id());
+        let doneid = format!("pickerdone{}", ctx.id());
+        let mut filter = String::new();
+        let mut page = 0;
+
+        loop {
+            // 1. Filter the options and show the current page
+            let filtered: Vec<&PickerOption> = self
+                .options
+                .iter()
+                .filter(|option| option.matches(&filter))
+                .collect();
+            let pagecount = filtered.len().div_ceil(PAGESIZE).max(1);
+            page = page.min(pagecount - 1);
+            let visible = &filtered[(page * PAGESIZE).min(filtered.len())
+                ..((page + 1) * PAGESIZE).min(filtered.len())];
+            // Only lists that do not fit in one select menu get the page, search and done buttons
+            let paged = self.options.len() > PAGESIZE;
+
+            let mut lines = vec![self.content.clone()];
+            if paged {
+                lines.push(format!(
+                    "Page {} of {pagecount}, {} options",
+                    page + 1,
+                    filtered.len()
+                ));
+            }
+            if !filter.is_empty() {
+                lines.push(format!("Searching for `{filter}`"));
+            }
+            if self.multiple && paged {
+                lines.push(format!("{} selected", self.selected.len()));
+            }
+            if visible.is_empty() {
+                lines.push("Nothing matches the search".to_string());
+            }
+
+            let mut components = vec![];
+            if !visible.is_empty() {
+                let options: Vec<CreateSelectMenuOption> = visible
+                    .iter()
+                    .map(|option| {
+                        let mut menuoption =
+                            CreateSelectMenuOption::new(&option.label, &option.value)
+                                .default_selection(self.selected.contains(&option.value));
+                        if let Some(description) = &option.description {
+                            menuoption = menuoption.description(description);
+                        }
+                        menuoption
+                    })
+                    .collect();
+                let mut selectmenu =
+                    CreateSelectMenu::new(&selectid, CreateSelectMenuKind::String { options })
+                        .placeholder(&self.placeholder);
+                if self.multiple {
+                    // The page holds at most 25 options, so this always fits in a u8
+                    selectmenu = selectmenu
+                        .min_values(0)
+                        .max_values(u8::try_from(visible.len()).unwrap_or(25));
+                } else {
+                    selectmenu = selectmenu.max_values(1);
+                }
+                components.push(CreateActionRow::SelectMenu(selectmenu));
+            }
+
+            let mut navigation = vec![];
+            if paged {
+                navigation.push(
+                    CreateButton::new(&previousid)
+                        .label("Previous")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(page == 0),
+                );
+                navigation.push(
+                    CreateButton::new(&nextid)
+                        .label("Next")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(page + 1 >= pagecount),
+                );
+                navigation.push(
+                    CreateButton::new(&searchid)
+                        .label("Search")
+                        .style(ButtonStyle::Primary),
+                );
+                if self.multiple {
+                    navigation.push(
+                        CreateButton::new(&doneid)
+                            .label("Done")
+                            .style(ButtonStyle::Success),
+                    );
+                }
+            }
+            if !navigation.is_empty() {
+                components.push(CreateActionRow::Buttons(navigation));
+            }
+            if !self.buttons.is_empty() {
+                components.push(CreateActionRow::Buttons(self.buttons.clone()));
+            }
+
+            let Some(interaction) = menu.page(lines.join("\n"), components).await? else {
+                return Ok(None);
+            };
+
+            // 2. Move between pages or change the filter with the buttons
+            let customid = interaction.data.custom_id.as_str();
+            if customid == previousid {
+                page = page.saturating_sub(1);
+                continue;
+            }
+            if customid == nextid {
+                page += 1;
+                continue;
+            }
+            if customid == searchid {
+                let Some(search) = menu.modal::<PickerSearch>(interaction).await? else {
+                    return Ok(None);
+                };
+                filter = search.filter.unwrap_or_default().trim().to_string();
+                page = 0;
+                continue;
+            }
+
+            // 3. Return the choice, the whole selection or the extra button
+            if customid == doneid {
+                let mut values: Vec<String> = self.selected.into_iter().collect();
+                values.sort();
+                return Ok(Some(Picked::Values(values)));
+            }
+            let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind
+            else {
+                return Ok(Some(Picked::Button(customid.to_string())));
+            };
+            if !self.multiple || !paged {
+                return Ok(Some(Picked::Values(values.clone())));
+            }
+            // Replace the selection of this page, the other pages keep theirs
+            for option in visible {
+                self.selected.remove(&option.value);
+            }
+            self.selected.extend(values.iter().cloned());
+        }
+    }
+}
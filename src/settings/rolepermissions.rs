@@ -0,0 +1,169 @@
+#![cfg(feature = "database")]
+use crate::settings::dbstructs::{Capability, CapabilityGrant, GuildPermissions};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
+use poise::serenity_prelude::{
+    ComponentInteractionDataKind, CreateActionRow, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption,
+};
+
+use super::commonfunctions::{admincheck, getguildid, ownercheck};
+
+/// Give roles and members without the administrator permission the right to change some settings.
+/// Only available for administrators and owners.
+//
+// 1. Choose a capability
+// 2. Choose the roles that get it
+// 3. Choose the members that get it
+// 4. Save the grant
+#[allow(clippy::too_many_lines)]
+pub async fn rolepermissions(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    if !ownercheck(ctx, None).await?
+        && !admincheck(ctx, Some("Only administrators can give permissions")).await?
+    {
+        return Ok(());
+    }
+    let guildid = getguildid(ctx).await?;
+    let dbpermissions: Option<GuildPermissions> =
+        DB.select(("guildpermissions", guildid.to_string())).await?;
+    let mut permissions = match dbpermissions {
+        Some(permissions) => permissions,
+        None => GuildPermissions {
+            guildid,
+            grants: vec![],
+        },
+    };
+
+    // 1. Choose a capability
+    let options: Vec<CreateSelectMenuOption> = Capability::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, capability)| {
+            let (roles, users) = permissions
+                .grants
+                .iter()
+                .find(|grant| grant.capability == *capability)
+                .map_or((0, 0), |grant| (grant.roles.len(), grant.users.len()));
+            CreateSelectMenuOption::new(capability.name(), index.to_string())
+                .description(format!("Given to {roles} roles and {users} members"))
+        })
+        .collect();
+    let capabilitymenuid = format!("rolepermissionscapability{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            "Which permission do you want to give?",
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    capabilitymenuid.clone(),
+                    CreateSelectMenuKind::String { options },
+                )
+                .max_values(1)
+                .placeholder("No permission chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let Some(capability) = values
+        .first()
+        .and_then(|x| x.parse::<usize>().ok())
+        .and_then(|x| Capability::ALL.get(x).copied())
+    else {
+        return Err("unknown capability chosen".into());
+    };
+    let current = permissions
+        .grants
+        .iter()
+        .find(|grant| grant.capability == capability)
+        .cloned()
+        .unwrap_or(CapabilityGrant {
+            capability,
+            roles: vec![],
+            users: vec![],
+        });
+
+    // 2. Choose the roles
+    let rolemenuid = format!("rolepermissionsroles{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            format!("Select the roles that get *{}*", capability.name()),
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    rolemenuid.clone(),
+                    CreateSelectMenuKind::Role {
+                        default_roles: Some(current.roles.clone()),
+                    },
+                )
+                .min_values(0)
+                .max_values(25)
+                .placeholder("No roles chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::RoleSelect { values: roles } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let roles = roles.clone();
+
+    // 3. Choose the members
+    let usermenuid = format!("rolepermissionsusers{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            format!(
+                "Select the members that get *{}*, besides the roles",
+                capability.name()
+            ),
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    usermenuid.clone(),
+                    CreateSelectMenuKind::User {
+                        default_users: Some(current.users.clone()),
+                    },
+                )
+                .min_values(0)
+                .max_values(25)
+                .placeholder("No members chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::UserSelect { values: users } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let users = users.clone();
+
+    // 4. Save the grant
+    let summary = format!(
+        "*{}* is given to {} roles and {} members",
+        capability.name(),
+        roles.len(),
+        users.len()
+    );
+    permissions
+        .grants
+        .retain(|grant| grant.capability != capability);
+    if !roles.is_empty() || !users.is_empty() {
+        permissions.grants.push(CapabilityGrant {
+            capability,
+            roles,
+            users,
+        });
+    }
+    let _: Option<GuildPermissions> = DB
+        .update(("guildpermissions", guildid.to_string()))
+        .content(permissions)
+        .await?;
+
+    menu.finish(summary).await?;
+    Ok(())
+}
@@ -1,162 +1,377 @@
 #![cfg(feature = "database")]
 #![allow(clippy::module_name_repetitions)]
-use crate::settings::commonfunctions::{admincheck, getguildid, ownercheck};
-use crate::settings::dbstructs::AvailableSettings;
+use crate::commands::price::Coins;
+use crate::settings::commonfunctions::{
+    administratorcheck, alertscheck, availablesettings, botownercheck, capabilities, commandscheck,
+    databasecheck, getguildid, isadmin, managetokenscheck, ownercheck, schedulescheck,
+    settingscheck,
+};
+use crate::settings::dbstructs::Capability;
+use crate::settings::settingsmenu::SettingsMenu;
 use crate::{Context, Error, DB};
 use poise::serenity_prelude::{
-    CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+    ComponentInteractionDataKind, CreateActionRow, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption,
 };
-use poise::CreateReply;
+
+/// Who can change a setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Requirement {
+    Capability(Capability),
+    Administrator,
+    Owner,
+}
+
+/// The settings in the overview: the subcommand of `/settings` that opens it, its description and who can change it.
+const SETTINGS: [(&str, &str, Requirement); 14] = [
+    (
+        "commands",
+        "Activate or deactivate available slash commands",
+        Requirement::Capability(Capability::Commands),
+    ),
+    (
+        "tokens",
+        "Add a token of which the price can be fetched",
+        Requirement::Capability(Capability::Tokens),
+    ),
+    (
+        "autodetect",
+        "Reply to contract addresses in chosen channels",
+        Requirement::Capability(Capability::Alerts),
+    ),
+    (
+        "ticker",
+        "Show the price of a token in the nickname of the bot",
+        Requirement::Capability(Capability::Schedules),
+    ),
+    (
+        "statchannels",
+        "Show the price of a token in a channel name",
+        Requirement::Capability(Capability::Schedules),
+    ),
+    (
+        "digests",
+        "Post a digest of the tokens on a schedule",
+        Requirement::Capability(Capability::Schedules),
+    ),
+    (
+        "boostfeed",
+        "Post newly boosted tokens in a channel",
+        Requirement::Capability(Capability::Alerts),
+    ),
+    (
+        "newpairs",
+        "Post newly created pairs in a channel",
+        Requirement::Capability(Capability::Alerts),
+    ),
+    (
+        "marketalerts",
+        "Warn about volume spikes and liquidity drops",
+        Requirement::Capability(Capability::Alerts),
+    ),
+    (
+        "permissions",
+        "Give roles or members permission to change settings",
+        Requirement::Administrator,
+    ),
+    (
+        "owner commands",
+        "(De)Activate commands available to turn on for guilds",
+        Requirement::Owner,
+    ),
+    (
+        "owner permissions",
+        "Change the permissions a guild has",
+        Requirement::Owner,
+    ),
+    (
+        "owner verify",
+        "Choose which tokens are verified",
+        Requirement::Owner,
+    ),
+    (
+        "owner requests",
+        "Review requests to add tokens to all servers",
+        Requirement::Owner,
+    ),
+];
 
 /// Change settings depending on your server
-//
-// 1. Check for Admins or Owners
-// 2. Get the GuildId, which is used to get the settings set for a guild
-// 3. Retrieve the available commands for a GuildId and put them in a vec, or fall back to default
-// 4. Open a context menu with the available settings menu
-// 5. Delete context menu and start that setting
-#[allow(clippy::too_many_lines)]
+#[poise::command(
+    slash_command,
+    subcommands(
+        "overview",
+        "commands",
+        "tokens",
+        "autodetect",
+        "ticker",
+        "statchannels",
+        "digests",
+        "boostfeed",
+        "newpairs",
+        "marketalerts",
+        "permissions",
+        "owner"
+    ),
+    subcommand_required,
+    check = "databasecheck",
+    check = "settingscheck"
+)]
+#[allow(clippy::unused_async)]
+pub async fn settings(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show all settings you can change in this server
 #[poise::command(slash_command)]
-pub async fn settings(ctx: Context<'_>) -> Result<(), Error> {
-    let admincheck = admincheck(ctx, None).await?;
-    let ownercheck = ownercheck(ctx, None).await?;
+pub async fn overview(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, None).await
+}
 
-    // Check if the author is the bot owner or an admin of that server.
-    if !admincheck && !ownercheck {
-        ctx.send(
-            CreateReply::default()
-                .content(
-                    "Sorry, you are not allowed to use this command.
-        \nOnly administrators and bot owners are.",
-                )
-                .ephemeral(true),
-        )
-        .await?;
+/// Turn commands on and off in this server
+#[poise::command(slash_command, check = "commandscheck")]
+pub async fn commands(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("commands")).await
+}
 
-        return Ok(());
-    };
-    ctx.send(
-        CreateReply::default()
-            .content("Opening settings menu")
-            .ephemeral(true),
-    )
-    .await?;
+/// Add a token of which the price can be fetched
+#[poise::command(slash_command, check = "managetokenscheck")]
+pub async fn tokens(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("tokens")).await
+}
 
-    let guildid = getguildid(ctx).await?;
+/// Reply to contract addresses in chosen channels
+#[poise::command(slash_command, check = "alertscheck")]
+pub async fn autodetect(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("autodetect")).await
+}
 
-    let dbcommandpermissions: Option<AvailableSettings> = DB
-        .select(("availablesettings", guildid.to_string()))
-        .await?;
-    let mut commandpermissions: AvailableSettings = match dbcommandpermissions {
-        Some(permissions) => permissions,
-        None => AvailableSettings {
-            availablecommands: true,
-            owneravailablecommands: false,
-            tokenpricetracking: true,
-            globaltokens: false,
-        },
-    };
-    if ownercheck {
-        commandpermissions = AvailableSettings {
-            availablecommands: true,
-            owneravailablecommands: true,
-            tokenpricetracking: true,
-            globaltokens: true,
-        }
-    };
+/// Show the price of a token in the nickname of the bot
+#[poise::command(slash_command, check = "schedulescheck")]
+pub async fn ticker(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("ticker")).await
+}
 
-    let mut selectmenuvec = vec![];
+/// Show the price of a token in a channel name
+#[poise::command(slash_command, check = "schedulescheck")]
+pub async fn statchannels(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("statchannels")).await
+}
 
-    if commandpermissions.availablecommands {
-        selectmenuvec.push(CreateSelectMenuOption::new(
-            "Activate or deactivate available slash commands",
-            "availablecommands",
-        ));
-    }
-    if commandpermissions.tokenpricetracking {
-        selectmenuvec.push(CreateSelectMenuOption::new(
-            "Add a token of which the price can be fetched",
-            "tokenpricetracking",
-        ));
-    }
-    if commandpermissions.owneravailablecommands {
-        selectmenuvec.push(CreateSelectMenuOption::new(
-            "(De)Activate commands available to turn on for guilds",
-            "owneravailablecommands",
-        ));
+/// Post a digest of the tokens on a schedule
+#[poise::command(slash_command, check = "schedulescheck")]
+pub async fn digests(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("digests")).await
+}
+
+/// Post newly boosted tokens in a channel
+#[poise::command(slash_command, check = "alertscheck")]
+pub async fn boostfeed(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("boostfeed")).await
+}
+
+/// Post newly created pairs in a channel
+#[poise::command(slash_command, check = "alertscheck")]
+pub async fn newpairs(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("newpairs")).await
+}
+
+/// Warn about volume spikes and liquidity drops
+#[poise::command(slash_command, check = "alertscheck")]
+pub async fn marketalerts(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("marketalerts")).await
+}
+
+/// Give roles or members permission to change settings
+#[poise::command(slash_command, check = "administratorcheck")]
+pub async fn permissions(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("permissions")).await
+}
+
+/// Settings of the bot owner
+#[poise::command(
+    slash_command,
+    subcommands("ownercommands", "ownerpermissions", "ownerverify", "ownerrequests"),
+    subcommand_required,
+    check = "botownercheck"
+)]
+#[allow(clippy::unused_async)]
+pub async fn owner(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Change which commands guilds can turn on
+#[poise::command(slash_command, rename = "commands")]
+pub async fn ownercommands(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("owner commands")).await
+}
+
+/// Change the permissions a guild has
+#[poise::command(slash_command, rename = "permissions")]
+pub async fn ownerpermissions(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("owner permissions")).await
+}
+
+/// Choose which tokens are verified
+#[poise::command(slash_command, rename = "verify")]
+pub async fn ownerverify(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("owner verify")).await
+}
+
+/// Review requests to add tokens to all servers
+#[poise::command(slash_command, rename = "requests")]
+pub async fn ownerrequests(ctx: Context<'_>) -> Result<(), Error> {
+    opensetting(ctx, Some("owner requests")).await
+}
+
+/// Open a setting in a private menu, or the overview if no setting is given.
+/// Going back from a setting shows the overview, so another setting can be chosen.
+async fn opensetting(ctx: Context<'_>, setting: Option<&'static str>) -> Result<(), Error> {
+    let mut menu = SettingsMenu::new(ctx, setting.unwrap_or("overview"));
+    let mut setting = setting;
+    loop {
+        let chosen = match setting.take() {
+            Some(chosen) => chosen,
+            None => {
+                menu.setcommand("overview");
+                match overviewpage(&mut menu).await? {
+                    Some(chosen) => chosen,
+                    None => return Ok(()),
+                }
+            }
+        };
+        menu.setcommand(chosen);
+        runsetting(&mut menu, chosen).await?;
+        if !menu.wentback() {
+            return Ok(());
+        }
     }
-    if commandpermissions.owneravailablecommands {
-        selectmenuvec.push(CreateSelectMenuOption::new(
-            "Change the permissions a user has",
-            "permissionsettings",
-        ));
+}
+
+/// Show the settings the author is allowed to change, with a summary of this server
+//
+// 1. Get the capabilities of the author and the permissions of the guild
+// 2. Put the settings the author can change in a select menu
+// 3. Return the chosen setting
+async fn overviewpage(menu: &mut SettingsMenu<'_>) -> Result<Option<&'static str>, Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+    let ownercheck = ownercheck(ctx, None).await?;
+    let admincheck = ownercheck || isadmin(ctx).await;
+    let capabilities = capabilities(ctx).await?;
+    let guildsettings = availablesettings(ctx, guildid).await?;
+
+    let options: Vec<CreateSelectMenuOption> = SETTINGS
+        .iter()
+        .filter(|(setting, _, requirement)| {
+            let allowed = match requirement {
+                Requirement::Capability(capability) => capabilities.contains(capability),
+                Requirement::Administrator => admincheck,
+                Requirement::Owner => ownercheck,
+            };
+            allowed
+                && (*setting != "commands" || guildsettings.availablecommands)
+                && (*setting != "tokens" || guildsettings.tokenpricetracking)
+        })
+        .map(|(setting, description, _)| CreateSelectMenuOption::new(*description, *setting))
+        .collect();
+    if options.is_empty() {
+        menu.finish("There are no settings you can change in this server")
+            .await?;
+        return Ok(None);
     }
 
-    let customid = format!("SettingsMenu{}", ctx.id());
-    let message = ctx
-        .channel_id()
-        .send_message(
-            ctx,
-            CreateMessage::new()
-                .content("Please click the setting you want to change")
-                .select_menu(
-                    CreateSelectMenu::new(
-                        "settings_menu",
-                        CreateSelectMenuKind::String {
-                            options: selectmenuvec,
-                        },
-                    )
-                    .custom_id(customid.clone())
+    let coins: Vec<Coins> = DB.select("Coins").await?;
+    let guildtokens = coins.iter().filter(|coin| coin.guildid == guildid).count();
+    let permissionnames: Vec<&str> = capabilities.iter().map(|x| x.name()).collect();
+    let summary = format!(
+        "**Settings of {}**\nYour permissions: {}\nTokens added by this server: {guildtokens}\n\nChoose the setting you want to change",
+        guildid.name(ctx.cache()).unwrap_or(guildid.to_string()),
+        permissionnames.join(", ")
+    );
+
+    let customid = format!("settingsoverview{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            summary,
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(customid, CreateSelectMenuKind::String { options })
                     .max_values(1)
-                    .placeholder("No Setting chosen"),
-                ),
+                    .placeholder("No setting chosen"),
+            )],
         )
-        .await?;
-    let interaction = match message
-        .await_component_interaction(&ctx.serenity_context().shard)
-        .timeout(std::time::Duration::from_secs(60 * 3))
-        .author_id(ctx.author().id)
-        .custom_ids(vec![customid])
-        .await
-    {
-        Some(x) => x,
-        None => {
-            message.reply(&ctx, "Timed out").await?;
-            return Ok(());
+        .await?
+    else {
+        // There is nothing to go back to from the overview
+        if menu.wentback() {
+            menu.finish("Closed the settings").await?;
         }
+        return Ok(None);
     };
-
-    message.delete(ctx).await?;
-
-    let interactionvalue = match &interaction.data.kind {
-        poise::serenity_prelude::ComponentInteractionDataKind::StringSelect { values } => {
-            &values[0]
-        }
-        _ => panic!("unexpected interaction data kind"),
+    let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
     };
+    Ok(SETTINGS
+        .iter()
+        .find(|(setting, _, _)| Some(*setting) == values.first().map(String::as_str))
+        .map(|(setting, _, _)| *setting))
+}
 
-    match interactionvalue.as_str() {
-        "availablecommands" => {
-            crate::settings::commandselection::ownercheckcommandselection(ctx).await?;
+/// Start the flow of a setting in the menu.
+async fn runsetting(menu: &mut SettingsMenu<'_>, setting: &str) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+    let guildsettings = availablesettings(ctx, guildid).await?;
+    match setting {
+        "commands" if guildsettings.availablecommands => {
+            crate::settings::commandselection::ownercheckcommandselection(menu).await?;
         }
-        "owneravailablecommands" => {
-            crate::settings::owneravailablecommands::ownercheckavailablecommands(ctx).await?;
+        "tokens" if guildsettings.tokenpricetracking => {
+            crate::commands::addtoken::addtoken(menu, guildid, guildsettings.globaltokens).await?;
         }
-        "tokenpricetracking" => {
-            crate::commands::addtoken::addtoken(
-                ctx,
-                interaction,
-                guildid,
-                commandpermissions.globaltokens,
-            )
-            .await?;
+        "commands" | "tokens" => {
+            menu.finish("The bot owner turned this setting off for this server")
+                .await?;
+        }
+        "autodetect" => {
+            crate::settings::autodetectsettings::autodetectsettings(menu).await?;
+        }
+        "ticker" => {
+            crate::settings::tickersettings::tickersettings(menu).await?;
+        }
+        "statchannels" => {
+            crate::settings::statchannelsettings::statchannelsettings(menu).await?;
+        }
+        "digests" => {
+            crate::settings::digestsettings::digestsettings(menu).await?;
+        }
+        "boostfeed" => {
+            crate::settings::boostfeedsettings::boostfeedsettings(menu).await?;
+        }
+        "newpairs" => {
+            crate::settings::newpairsettings::newpairsettings(menu).await?;
+        }
+        "marketalerts" => {
+            crate::settings::marketalertsettings::marketalertsettings(menu).await?;
+        }
+        "permissions" => {
+            crate::settings::rolepermissions::rolepermissions(menu).await?;
+        }
+        "owner commands" => {
+            crate::settings::owneravailablecommands::ownercheckavailablecommands(menu).await?;
+        }
+        "owner permissions" => {
+            crate::settings::permissionsettings::permissionsettings(menu).await?;
+        }
+        "owner verify" => {
+            crate::settings::verifytokens::verifytokens(menu).await?;
         }
-        "permissionsettings" => {
-            crate::settings::permissionsettings::permissionsettings(ctx).await?;
+        "owner requests" => {
+            crate::settings::tokenrequests::tokenrequests(menu).await?;
         }
         _ => {
             return Err(format!(
-                "There is no implementation available for this setting: {interactionvalue}."
+                "There is no implementation available for this setting: {setting}."
             )
             .into())
         }
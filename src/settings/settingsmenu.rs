@@ -0,0 +1,161 @@
+#![cfg(feature = "database")]
+use crate::{Context, Error};
+use poise::serenity_prelude::{
+    ButtonStyle, ComponentInteraction, CreateActionRow, CreateButton, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+use poise::{CreateReply, Modal, ReplyHandle};
+
+/// Seconds the author has to use a page before the menu closes.
+const PAGETIMEOUT: u64 = 60 * 3;
+
+/// A private message in which the pages of a settings flow are shown one after the other.
+/// Every page gets a row with a back and a cancel button, back returns to the overview of `/settings`.
+pub struct SettingsMenu<'a> {
+    pub ctx: Context<'a>,
+    /// The subcommand of `/settings` that opens the current setting, so the author knows how to continue after a timeout
+    command: &'static str,
+    handle: Option<ReplyHandle<'a>>,
+    /// The last interaction with the menu that is not answered yet, it is answered by showing the next page
+    pending: Option<ComponentInteraction>,
+    back: bool,
+}
+
+impl<'a> SettingsMenu<'a> {
+    pub fn new(ctx: Context<'a>, command: &'static str) -> Self {
+        SettingsMenu {
+            ctx,
+            command,
+            handle: None,
+            pending: None,
+            back: false,
+        }
+    }
+
+    /// Change the subcommand that is suggested after a timeout, when the author moves to another setting.
+    pub fn setcommand(&mut self, command: &'static str) {
+        self.command = command;
+        self.back = false;
+    }
+
+    /// Whether the author pressed the back button on the last page.
+    pub fn wentback(&self) -> bool {
+        self.back
+    }
+
+    /// Show a page and wait for the author to use one of its components.
+    /// `content` should summarize the current state of the setting.
+    /// Returns `None` when the author went back, cancelled or did not respond in time, the caller should stop then.
+    pub async fn page(
+        &mut self,
+        content: impl Into<String>,
+        mut components: Vec<CreateActionRow>,
+    ) -> Result<Option<ComponentInteraction>, Error> {
+        let ctx = self.ctx;
+        let backbuttonid = format!("settingsback{}", ctx.id());
+        let cancelbuttonid = format!("settingscancel{}", ctx.id());
+        components.push(CreateActionRow::Buttons(vec![
+            CreateButton::new(backbuttonid.clone())
+                .label("Back")
+                .style(ButtonStyle::Secondary),
+            CreateButton::new(cancelbuttonid.clone())
+                .label("Cancel")
+                .style(ButtonStyle::Secondary),
+        ]));
+        self.show(content.into(), components).await?;
+
+        let Some(handle) = &self.handle else {
+            return Err("The settings menu was not sent".into());
+        };
+        let message = handle.message().await?.into_owned();
+        let Some(interaction) = message
+            .await_component_interaction(&ctx.serenity_context().shard)
+            .timeout(std::time::Duration::from_secs(PAGETIMEOUT))
+            .author_id(ctx.author().id)
+            .await
+        else {
+            self.timedout().await?;
+            return Ok(None);
+        };
+
+        self.pending = Some(interaction.clone());
+        if interaction.data.custom_id == backbuttonid {
+            self.back = true;
+            return Ok(None);
+        }
+        if interaction.data.custom_id == cancelbuttonid {
+            self.finish("Closed the settings").await?;
+            return Ok(None);
+        }
+        Ok(Some(interaction))
+    }
+
+    /// Open a modal on an interaction of the last page.
+    /// The page stays visible and is replaced by the next page or `finish`.
+    pub async fn modal<M: Modal>(
+        &mut self,
+        interaction: ComponentInteraction,
+    ) -> Result<Option<M>, Error> {
+        // The modal answers the interaction, so the next page edits the message instead
+        self.pending = None;
+        let response = poise::execute_modal_on_component_interaction::<M>(
+            self.ctx.serenity_context(),
+            interaction,
+            None,
+            None,
+        )
+        .await?;
+        if response.is_none() {
+            self.timedout().await?;
+        }
+        Ok(response)
+    }
+
+    /// Replace the page with the result of the setting, without components.
+    pub async fn finish(&mut self, content: impl Into<String>) -> Result<(), Error> {
+        self.show(content.into(), vec![]).await
+    }
+
+    /// Tell the author how to continue after they did not respond in time.
+    async fn timedout(&mut self) -> Result<(), Error> {
+        self.show(
+            format!(
+                "Timed out, use `/settings {}` to continue where you left off",
+                self.command
+            ),
+            vec![],
+        )
+        .await
+    }
+
+    /// Send the menu, or update it with a new page.
+    async fn show(
+        &mut self,
+        content: String,
+        components: Vec<CreateActionRow>,
+    ) -> Result<(), Error> {
+        let ctx = self.ctx;
+        if let Some(interaction) = self.pending.take() {
+            interaction
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .content(content)
+                            .components(components),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+        let reply = CreateReply::default()
+            .content(content)
+            .components(components)
+            .ephemeral(true);
+        match &self.handle {
+            Some(handle) => handle.edit(ctx, reply).await?,
+            None => self.handle = Some(ctx.send(reply).await?),
+        }
+        Ok(())
+    }
+}
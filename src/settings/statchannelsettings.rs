@@ -0,0 +1,157 @@
+#![cfg(feature = "database")]
+use crate::settings::dbstructs::{StatChannel, StatDisplay};
+use crate::settings::picker::{Picked, Picker, PickerOption};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelType, ComponentInteractionDataKind, CreateActionRow, CreateButton,
+    CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+};
+
+use super::commonfunctions::{getguildid, guildcoins};
+
+/// Choose a channel that is renamed to show the price or market cap of a token.
+//
+// 1. Choose the channel
+// 2. Choose the token, or stop showing a price in that channel
+// 3. Choose whether to show the price or the market cap
+#[allow(clippy::too_many_lines)]
+pub async fn statchannelsettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+    let coins = guildcoins(guildid).await?;
+    if coins.is_empty() {
+        menu.finish("There are no tokens in this guild yet, add one first with the token setting")
+            .await?;
+        return Ok(());
+    }
+
+    let statchannels: Vec<StatChannel> = DB.select("statchannels").await?;
+    let currentchannels: Vec<String> = statchannels
+        .iter()
+        .filter(|statchannel| statchannel.guildid == guildid)
+        .map(|statchannel| format!("<#{}>: {}", statchannel.channelid, statchannel.name))
+        .collect();
+
+    // 1. Choose the channel
+    let channelmenuid = format!("statchannelchannel{}", ctx.id());
+    let Some(interaction) = menu.page(format!(
+                    "Select the channel that should show a price. Locked voice channels work best.\nCurrent stat channels:\n{}",
+                    if currentchannels.is_empty() {
+                        "None".to_string()
+                    } else {
+                        currentchannels.join("\n")
+                    }
+                ), vec![CreateActionRow::SelectMenu(CreateSelectMenu::new(
+                        channelmenuid.clone(),
+                        CreateSelectMenuKind::Channel {
+                            channel_types: Some(vec![ChannelType::Voice, ChannelType::Stage]),
+                            default_channels: None,
+                        },
+                    )
+                    .max_values(1)
+                    .placeholder("No channel chosen"))]).await? else {
+        return Ok(());
+    };
+    let ComponentInteractionDataKind::ChannelSelect { values } = &interaction.data.kind else {
+        return Err("unexpected interaction data kind".into());
+    };
+    let Some(channelid) = values.first().copied() else {
+        return Ok(());
+    };
+
+    // 2. Choose the token, or stop showing a price
+    let removebuttonid = format!("statchannelremove{}", ctx.id());
+    let coinoptions: Vec<PickerOption> = coins
+        .iter()
+        .map(|coin| PickerOption::new(&coin.name, &coin.name).description(&coin.address))
+        .collect();
+    let Some(picked) = Picker::new(
+        format!("Select the token shown in <#{channelid}>"),
+        coinoptions,
+    )
+    .placeholder("No token chosen")
+    .button(
+        CreateButton::new(&removebuttonid)
+            .label("Stop showing a price in this channel")
+            .style(ButtonStyle::Danger),
+    )
+    .pick(menu)
+    .await?
+    else {
+        return Ok(());
+    };
+    let coin = match picked {
+        Picked::Values(values) => {
+            match coins.iter().find(|coin| Some(&coin.name) == values.first()) {
+                Some(coin) => coin,
+                None => return Err("The chosen token does not exist anymore".into()),
+            }
+        }
+        Picked::Button(buttonid) if buttonid == removebuttonid => {
+            let _: Option<StatChannel> = DB.delete(("statchannels", channelid.to_string())).await?;
+            menu.finish(format!("<#{channelid}> does not show a price anymore"))
+                .await?;
+            return Ok(());
+        }
+        Picked::Button(_) => return Err("unexpected button".into()),
+    };
+
+    // 3. Choose what to show
+    let displaymenuid = format!("statchanneldisplay{}", ctx.id());
+    let Some(interaction) = menu
+        .page(
+            format!("Select what <#{channelid}> shows"),
+            vec![CreateActionRow::SelectMenu(
+                CreateSelectMenu::new(
+                    displaymenuid.clone(),
+                    CreateSelectMenuKind::String {
+                        options: vec![
+                            CreateSelectMenuOption::new(
+                                format!("Price ({}: $0.42)", coin.name),
+                                "price",
+                            ),
+                            CreateSelectMenuOption::new(
+                                format!("Market cap ({} MC: $1.2M)", coin.name),
+                                "marketcap",
+                            ),
+                        ],
+                    },
+                )
+                .max_values(1)
+                .placeholder("Nothing chosen"),
+            )],
+        )
+        .await?
+    else {
+        return Ok(());
+    };
+    let display = match &interaction.data.kind {
+        ComponentInteractionDataKind::StringSelect { values }
+            if values.first().is_some_and(|x| x == "marketcap") =>
+        {
+            StatDisplay::MarketCap
+        }
+        ComponentInteractionDataKind::StringSelect { .. } => StatDisplay::Price,
+        _ => return Err("unexpected interaction data kind".into()),
+    };
+
+    let _: Option<StatChannel> = DB
+        .update(("statchannels", channelid.to_string()))
+        .content(StatChannel {
+            guildid,
+            channelid,
+            name: coin.name.clone(),
+            address: coin.address.clone(),
+            display,
+        })
+        .await?;
+
+    menu.finish(format!(
+                "<#{channelid}> will show the {} of {} within a few minutes. Discord only allows renaming a channel twice per 10 minutes, so it is updated at most once every 5 minutes.",
+                if display == StatDisplay::Price { "price" } else { "market cap" },
+                coin.name
+            )).await?;
+
+    Ok(())
+}
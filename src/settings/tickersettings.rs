@@ -0,0 +1,95 @@
+#![cfg(feature = "database")]
+use crate::settings::dbstructs::Ticker;
+use crate::settings::picker::{Picked, Picker, PickerOption};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{Error, DB};
+use poise::serenity_prelude::{ButtonStyle, CreateButton};
+
+use super::commonfunctions::{getguildid, guildcoins};
+
+/// Choose the token of which the price is shown in the nickname of the bot, or turn the ticker off.
+#[allow(clippy::too_many_lines)]
+pub async fn tickersettings(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    let guildid = getguildid(ctx).await?;
+
+    let currentticker: Option<Ticker> = DB.select(("ticker", guildid.to_string())).await?;
+    let coins = guildcoins(guildid).await?;
+    if coins.is_empty() {
+        menu.finish("There are no tokens in this guild yet, add one first with `/settings tokens`")
+            .await?;
+        return Ok(());
+    }
+
+    let options: Vec<PickerOption> = coins
+        .iter()
+        .map(|coin| PickerOption::new(&coin.name, &coin.name).description(&coin.address))
+        .collect();
+
+    let offbuttonid = format!("tickeroff{}", ctx.id());
+    let Some(picked) = Picker::new(
+        format!(
+            "Select the token that is shown in the nickname of the bot.\nThe ticker currently shows **{}**.",
+            match &currentticker {
+                Some(ticker) if ticker.enabled => ticker.name.as_str(),
+                _ => "nothing",
+            }
+        ),
+        options,
+    )
+    .placeholder("No token chosen")
+    .button(
+        CreateButton::new(&offbuttonid)
+            .label("Turn the ticker off")
+            .style(ButtonStyle::Danger),
+    )
+    .pick(menu)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let ticker = match picked {
+        Picked::Values(values) => {
+            let Some(coin) = coins.iter().find(|coin| Some(&coin.name) == values.first()) else {
+                return Err("The chosen token does not exist anymore".into());
+            };
+            Ticker {
+                guildid,
+                name: coin.name.clone(),
+                address: coin.address.clone(),
+                enabled: true,
+            }
+        }
+        Picked::Button(buttonid) if buttonid == offbuttonid => match currentticker {
+            Some(ticker) => Ticker {
+                enabled: false,
+                ..ticker
+            },
+            None => {
+                menu.finish("The ticker was already off").await?;
+                return Ok(());
+            }
+        },
+        Picked::Button(_) => return Err("unexpected button".into()),
+    };
+
+    let _: Option<Ticker> = DB
+        .update(("ticker", guildid.to_string()))
+        .content(&ticker)
+        .await?;
+
+    let text = if ticker.enabled {
+        format!(
+            "The nickname of the bot will show the price of {} within a few minutes",
+            ticker.name
+        )
+    } else {
+        // Give the bot its normal name back
+        guildid.edit_nickname(ctx, None).await?;
+        "The ticker is turned off".to_string()
+    };
+    menu.finish(text).await?;
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::settings::dbstructs::TokenRequest;
+use crate::settings::picker::{Picked, Picker, PickerOption};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::tokenstore::{store, StoredToken, TokenStore};
+use crate::{dexscreener, tokenindex, Error, DB};
+use poise::serenity_prelude::{ButtonStyle, CreateActionRow, CreateButton, CreateMessage};
+use poise::Modal;
+
+use super::commonfunctions::ownercheck;
+
+#[derive(Debug, Modal, Clone)]
+#[name = "Review token request"]
+struct ReviewModal {
+    #[name = "Reason, shown to the guild"]
+    #[placeholder = "Not an official contract address"]
+    #[paragraph]
+    reason: Option<String>,
+}
+
+/// Approve or reject the requests of guilds to add a token to all guilds. Only available for owners.
+//
+// 1. Choose a request from the queue, oldest first
+// 2. Approve or reject it
+// 3. Ask for the reason in a modal
+// 4. Add the token when approved, and tell the requesting guild the result
+#[allow(clippy::too_many_lines)]
+pub async fn tokenrequests(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    if !ownercheck(ctx, Some("You are not the bot owner!")).await? {
+        return Ok(());
+    }
+    let mut requests: Vec<TokenRequest> = DB.select("tokenrequests").await?;
+    if requests.is_empty() {
+        menu.finish("There are no token requests to review").await?;
+        return Ok(());
+    }
+    requests.sort_by_key(|request| request.requestedat);
+
+    // 1. Choose a request
+    let options: Vec<PickerOption> = requests
+        .iter()
+        .map(|request| {
+            PickerOption::new(
+                format!("{} (guild {})", request.name, request.guildid),
+                request.address.to_lowercase(),
+            )
+            .description(&request.address)
+        })
+        .collect();
+    let Some(Picked::Values(values)) = Picker::new(
+        format!(
+            "There are {} token requests. Select the request to review.",
+            requests.len()
+        ),
+        options,
+    )
+    .placeholder("No request chosen")
+    .pick(menu)
+    .await?
+    else {
+        return Ok(());
+    };
+    let Some(request) = requests
+        .into_iter()
+        .find(|request| Some(&request.address.to_lowercase()) == values.first())
+    else {
+        return Err("The chosen request does not exist anymore".into());
+    };
+
+    // 2. Approve or reject
+    let approvebuttonid = format!("tokenrequestapprove{}", ctx.id());
+    let rejectbuttonid = format!("tokenrequestreject{}", ctx.id());
+    let Some(interaction) = menu.page(format!(
+                    "**{}** `{}`\nRequested by <@{}> in guild {} <t:{}:R>\n<https://dexscreener.com/search?q={}>",
+                    request.name,
+                    request.address,
+                    request.requester,
+                    request.guildid,
+                    request.requestedat,
+                    request.address
+                ), vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new(approvebuttonid.clone())
+                        .label("Approve")
+                        .style(ButtonStyle::Success),
+                    CreateButton::new(rejectbuttonid.clone())
+                        .label("Reject")
+                        .style(ButtonStyle::Danger),
+                ])]).await? else {
+        return Ok(());
+    };
+    let approved = interaction.data.custom_id == approvebuttonid;
+
+    // 3. Ask for the reason
+    let Some(modalresponse) = menu.modal::<ReviewModal>(interaction).await? else {
+        return Ok(());
+    };
+    let reason = modalresponse
+        .reason
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty());
+
+    // 4. Add the token and notify the guild
+    if approved {
+        let existing: Option<Coins> = DB.select(("Coins", request.name.clone())).await?;
+        if existing.is_some() {
+            menu.finish(format!(
+                "There already is a token called {}, remove it first or reject this request",
+                request.name
+            ))
+            .await?;
+            return Ok(());
+        }
+        let chain = match &request.chain {
+            Some(chain) => Some(chain.clone()),
+            None => dexscreener::bestpair(&request.address)
+                .await?
+                .map(|pair| pair.chain_id),
+        };
+        store()
+            .insert(StoredToken {
+                name: request.name.clone(),
+                address: request.address.clone(),
+                guildid: Some(request.guildid),
+                global: true,
+                verified: false,
+                chain,
+            })
+            .await?;
+        tokenindex::tryrefresh().await;
+    }
+    let _: Option<TokenRequest> = DB
+        .delete(("tokenrequests", request.address.to_lowercase()))
+        .await?;
+
+    let mut notification = format!(
+        "The request of <@{}> to add **{}** to all servers is **{}**.",
+        request.requester,
+        request.name,
+        if approved { "approved" } else { "rejected" }
+    );
+    if let Some(reason) = &reason {
+        notification = format!("{notification}\nReason: {reason}");
+    }
+    if let Err(notifyerror) = request
+        .channelid
+        .send_message(ctx, CreateMessage::new().content(notification))
+        .await
+    {
+        println!(
+            "Could not notify guild {} about its token request: {notifyerror}",
+            request.guildid
+        );
+    }
+
+    menu.finish(format!(
+        "{} is {}",
+        request.name,
+        if approved {
+            "added to all servers"
+        } else {
+            "rejected"
+        }
+    ))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::settings::picker::{Picked, Picker, PickerOption};
+use crate::settings::settingsmenu::SettingsMenu;
+use crate::{tokenindex, Error, DB};
+
+use super::commonfunctions::ownercheck;
+
+/// Choose which tokens are verified. Verified tokens get a badge and can not be impersonated by tokens with the same symbol.
+/// Only available for owners.
+pub async fn verifytokens(menu: &mut SettingsMenu<'_>) -> Result<(), Error> {
+    let ctx = menu.ctx;
+    if !ownercheck(ctx, Some("You are not the bot owner!")).await? {
+        return Ok(());
+    }
+    let mut coins: Vec<Coins> = DB.select("Coins").await?;
+    if coins.is_empty() {
+        menu.finish("There are no tokens yet").await?;
+        return Ok(());
+    }
+    // Global tokens are the most likely to be impersonated, so show them first
+    coins.sort_by(|a, b| b.global.cmp(&a.global).then(a.name.cmp(&b.name)));
+
+    let options: Vec<PickerOption> = coins
+        .iter()
+        .map(|coin| PickerOption::new(&coin.name, &coin.name).description(&coin.address))
+        .collect();
+    let selected = coins
+        .iter()
+        .filter(|coin| coin.verified)
+        .map(|coin| coin.name.clone());
+    let Some(Picked::Values(values)) = Picker::new("Select the verified tokens", options)
+        .multiple(selected)
+        .placeholder("No verified tokens")
+        .pick(menu)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let mut verified = vec![];
+    for coin in coins {
+        let isverified = values.contains(&coin.name);
+        if isverified {
+            verified.push(coin.name.clone());
+        }
+        if isverified == coin.verified {
+            continue;
+        }
+        let _: Option<Coins> = DB
+            .update(("Coins", coin.name.clone()))
+            .content(Coins {
+                verified: isverified,
+                ..coin
+            })
+            .await?;
+    }
+
+    tokenindex::tryrefresh().await;
+
+    menu.finish(format!(
+        "Verified tokens: {}",
+        if verified.is_empty() {
+            "none".to_string()
+        } else {
+            verified.join(", ")
+        }
+    ))
+    .await?;
+    Ok(())
+}
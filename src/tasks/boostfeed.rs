@@ -0,0 +1,94 @@
+#![cfg(feature = "database")]
+use crate::commands::trending::boostcard;
+use crate::settings::dbstructs::{BoostFeed, SeenBoost};
+use crate::{dexscreener, Error, DB};
+use chrono::Utc;
+use poise::serenity_prelude::{self as serenity, CreateMessage};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Seconds between two checks for new boosts.
+const CHECKINTERVAL: u64 = 60;
+/// Seen boosts are forgotten after 2 days, so a token that is boosted again after that is posted again.
+const SEENRETENTION: i64 = 60 * 60 * 48;
+
+/// Post newly boosted tokens in the feed channels of all guilds.
+pub async fn boostfeed(ctx: serenity::Context) {
+    loop {
+        match postnewboosts(&ctx).await {
+            Ok(()) => {}
+            Err(boostfeederror) => println!("Could not update the boost feeds: {boostfeederror}"),
+        }
+        tokio::time::sleep(Duration::from_secs(CHECKINTERVAL)).await;
+    }
+}
+
+async fn postnewboosts(ctx: &serenity::Context) -> Result<(), Error> {
+    let feeds: Vec<BoostFeed> = DB.select("boostfeeds").await?;
+    if feeds.is_empty() {
+        return Ok(());
+    }
+    let now = Utc::now().timestamp();
+    DB.query("DELETE seenboosts WHERE seenat < $before")
+        .bind(("before", now - SEENRETENTION))
+        .await?;
+
+    let seenboosts: Vec<SeenBoost> = DB.select("seenboosts").await?;
+    // When nothing was seen before, only remember the current boosts instead of flooding the feeds
+    let firstrun = seenboosts.is_empty();
+    let mut seen: HashSet<(String, String)> = seenboosts
+        .into_iter()
+        .map(|seenboost| (seenboost.chainid, seenboost.tokenaddress))
+        .collect();
+
+    let mut newboosts = vec![];
+    for boost in dexscreener::latestboosts().await? {
+        // The same token can be in the latest boosts more than once
+        if !seen.insert((boost.chain_id.clone(), boost.token_address.clone())) {
+            continue;
+        }
+        let _: Option<SeenBoost> = DB
+            .create((
+                "seenboosts",
+                format!("{}:{}", boost.chain_id, boost.token_address),
+            ))
+            .content(SeenBoost {
+                chainid: boost.chain_id.clone(),
+                tokenaddress: boost.token_address.clone(),
+                seenat: now,
+            })
+            .await?;
+        if !firstrun {
+            newboosts.push(boost);
+        }
+    }
+    if newboosts.is_empty() {
+        return Ok(());
+    }
+
+    let addresses: Vec<String> = newboosts
+        .iter()
+        .map(|boost| boost.token_address.clone())
+        .collect();
+    let pairs = dexscreener::bestpairs(&addresses).await?;
+    for boost in &newboosts {
+        let embed = boostcard(boost, pairs.get(&boost.token_address.to_lowercase()));
+        for feed in &feeds {
+            if feed
+                .chain
+                .as_ref()
+                .is_some_and(|chain| *chain != boost.chain_id)
+            {
+                continue;
+            }
+            if let Err(posterror) = feed
+                .channelid
+                .send_message(ctx, CreateMessage::new().embed(embed.clone()))
+                .await
+            {
+                println!("Could not post a boost in {}: {posterror}", feed.channelid);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,305 @@
+#![cfg(feature = "database")]
+use crate::commands::price::Coins;
+use crate::dexscreener::{self, Pair};
+use crate::formatting::{self, Locale};
+use crate::settings::commonfunctions::guildcoins;
+use crate::settings::dbstructs::{Digest, DigestSchedule};
+use crate::{Error, DB};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use poise::serenity_prelude::{self as serenity, CreateEmbed, CreateEmbedFooter, CreateMessage};
+use std::time::Duration;
+
+/// Seconds between two checks whether a digest has to be posted.
+const CHECKINTERVAL: u64 = 60;
+/// Discord allows at most 25 fields in an embed.
+const MAXFIELDS: usize = 25;
+
+/// Post the digests of all guilds when they are due.
+pub async fn digests(ctx: serenity::Context) {
+    loop {
+        match postduedigests(&ctx).await {
+            Ok(()) => {}
+            Err(digesterror) => println!("Could not post the digests: {digesterror}"),
+        }
+        tokio::time::sleep(Duration::from_secs(CHECKINTERVAL)).await;
+    }
+}
+
+async fn postduedigests(ctx: &serenity::Context) -> Result<(), Error> {
+    let digests: Vec<Digest> = DB.select("digests").await?;
+    let now = Utc::now();
+
+    for digest in digests {
+        if digest.nextpost > now.timestamp() {
+            continue;
+        }
+        // Schedule the next post first, so a failing channel is not retried every minute
+        let timezone = parsetimezone(&digest.timezone).unwrap_or(Tz::UTC);
+        let Some(nextpost) = nextpost(digest.schedule, now, timezone) else {
+            continue;
+        };
+        let _: Option<Digest> = DB
+            .update(("digests", digest.channelid.to_string()))
+            .content(Digest {
+                nextpost: nextpost.timestamp(),
+                ..digest
+            })
+            .await?;
+
+        let embed = match digestembed(digest.guildid).await {
+            Ok(Some(embed)) => embed,
+            Ok(None) => continue,
+            Err(embederror) => {
+                println!(
+                    "Could not create the digest for {}: {embederror}",
+                    digest.guildid
+                );
+                continue;
+            }
+        };
+        if let Err(posterror) = digest
+            .channelid
+            .send_message(ctx, CreateMessage::new().embed(embed))
+            .await
+        {
+            println!(
+                "Could not post the digest in {}: {posterror}",
+                digest.channelid
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Create the digest embed with the price, change and volume of every token of a guild, and the top gainer and loser.
+/// Returns `None` if the guild has no tokens that are available on Dexscreener.
+pub async fn digestembed(guildid: serenity::GuildId) -> Result<Option<CreateEmbed>, Error> {
+    let coins = guildcoins(guildid).await?;
+    let addresses: Vec<String> = coins.iter().map(|coin| coin.address.clone()).collect();
+    let pairs = dexscreener::bestpairs(&addresses).await?;
+    let mut coinpairs: Vec<(&Coins, &Pair)> = coins
+        .iter()
+        .filter_map(|coin| Some((coin, pairs.get(&coin.address.to_lowercase())?)))
+        .collect();
+    if coinpairs.is_empty() {
+        return Ok(None);
+    }
+    let change = |pair: &Pair| {
+        pair.price_change
+            .as_ref()
+            .and_then(|x| x.h24)
+            .unwrap_or(0.0)
+    };
+    coinpairs.sort_by(|a, b| change(b.1).total_cmp(&change(a.1)));
+
+    let locale = Locale::from_env();
+    let mut description = vec![];
+    if let Some((gainer, gainerpair)) = coinpairs.first() {
+        description.push(format!(
+            "📈 Top gainer: **{}** {}",
+            gainer.name,
+            formatting::percentage(change(gainerpair), locale)
+        ));
+    }
+    // With one token the gainer would also be the loser
+    if let Some((loser, loserpair)) = coinpairs.last().filter(|_| coinpairs.len() >= 2) {
+        description.push(format!(
+            "📉 Top loser: **{}** {}",
+            loser.name,
+            formatting::percentage(change(loserpair), locale)
+        ));
+    }
+
+    let fields = coinpairs.iter().take(MAXFIELDS).map(|(coin, pair)| {
+        let price = match &pair.price_usd {
+            Some(val) => formatting::pricestr(val, locale),
+            None => "$N/A".to_string(),
+        };
+        let volume = pair.volume.as_ref().and_then(|x| x.h24).unwrap_or(0.0);
+        (
+            coin.name.clone(),
+            format!(
+                "{price} *({})*\nVol {}",
+                formatting::percentage(change(pair), locale),
+                formatting::compactusd(volume, locale)
+            ),
+            true,
+        )
+    });
+
+    Ok(Some(
+        CreateEmbed::default()
+            .title("Price digest")
+            .description(description.join("\n"))
+            .fields(fields)
+            .footer(CreateEmbedFooter::new(
+                "All rights reserved to Dexscreener.com",
+            ))
+            .timestamp(serenity::Timestamp::now()),
+    ))
+}
+
+/// Read a schedule written as a time for daily posts (`09:00`) or as an interval in hours (`4h`).
+pub fn parseschedule(text: &str) -> Option<DigestSchedule> {
+    let text = text.trim().to_lowercase();
+    if let Some(hours) = text.strip_suffix('h') {
+        let hours = hours.trim().parse::<u32>().ok()?;
+        // At most once a week
+        return (1..=168)
+            .contains(&hours)
+            .then_some(DigestSchedule::Interval { hours });
+    }
+    let (hour, minute) = text.split_once(':')?;
+    let hour = hour.trim().parse::<u32>().ok()?;
+    let minute = minute.trim().parse::<u32>().ok()?;
+    (hour < 24 && minute < 60).then_some(DigestSchedule::Daily { hour, minute })
+}
+
+/// Read an IANA timezone name like `Europe/Amsterdam`.
+pub fn parsetimezone(text: &str) -> Option<Tz> {
+    text.trim().parse::<Tz>().ok()
+}
+
+/// Calculate when a digest is posted next, after the supplied moment.
+pub fn nextpost(
+    schedule: DigestSchedule,
+    after: DateTime<Utc>,
+    timezone: Tz,
+) -> Option<DateTime<Utc>> {
+    match schedule {
+        DigestSchedule::Interval { hours } => {
+            Some(after + chrono::Duration::hours(i64::from(hours)))
+        }
+        DigestSchedule::Daily { hour, minute } => {
+            let localafter = after.with_timezone(&timezone);
+            let mut date = localafter.date_naive();
+            // Today if the time did not pass yet, otherwise tomorrow.
+            // Days on which the time does not exist because of daylight saving time are skipped.
+            for _ in 0..3 {
+                let localtime = date.and_hms_opt(hour, minute, 0)?;
+                if let Some(candidate) = timezone.from_local_datetime(&localtime).earliest() {
+                    if candidate > localafter {
+                        return Some(candidate.with_timezone(&Utc));
+                    }
+                }
+                date = date.succ_opt()?;
+            }
+            None
+        }
+    }
+}
+
+/// Describe a schedule for the settings menu, e.g. `every day at 09:00 (Europe/Amsterdam)`.
+pub fn describeschedule(schedule: DigestSchedule, timezone: &str) -> String {
+    match schedule {
+        DigestSchedule::Daily { hour, minute } => {
+            format!("every day at {hour:02}:{minute:02} ({timezone})")
+        }
+        DigestSchedule::Interval { hours: 1 } => "every hour".to_string(),
+        DigestSchedule::Interval { hours } => format!("every {hours} hours"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0)
+            .single()
+            .unwrap_or(DateTime::UNIX_EPOCH)
+    }
+
+    fn amsterdam() -> Tz {
+        parsetimezone("Europe/Amsterdam").unwrap_or(Tz::UTC)
+    }
+
+    #[test]
+    fn schedules() {
+        assert_eq!(
+            parseschedule(" 09:30 "),
+            Some(DigestSchedule::Daily {
+                hour: 9,
+                minute: 30
+            })
+        );
+        assert_eq!(
+            parseschedule("4H"),
+            Some(DigestSchedule::Interval { hours: 4 })
+        );
+        assert_eq!(
+            parseschedule("168h"),
+            Some(DigestSchedule::Interval { hours: 168 })
+        );
+        for invalid in ["0h", "169h", "24:00", "12:60", "noon", "", "-1h"] {
+            assert_eq!(parseschedule(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn timezones() {
+        assert_eq!(
+            parsetimezone(" Europe/Amsterdam "),
+            Some(Tz::Europe__Amsterdam)
+        );
+        assert_eq!(parsetimezone("UTC"), Some(Tz::UTC));
+        assert_eq!(parsetimezone("Europe/Nowhere"), None);
+    }
+
+    #[test]
+    fn interval() {
+        assert_eq!(
+            nextpost(
+                DigestSchedule::Interval { hours: 4 },
+                utc(6, 1, 22, 0),
+                amsterdam()
+            ),
+            Some(utc(6, 2, 2, 0))
+        );
+    }
+
+    #[test]
+    fn dailytodayortomorrow() {
+        let schedule = DigestSchedule::Daily { hour: 9, minute: 0 };
+        // 09:00 in Amsterdam is 07:00 UTC in summer
+        assert_eq!(
+            nextpost(schedule, utc(6, 1, 6, 0), amsterdam()),
+            Some(utc(6, 1, 7, 0))
+        );
+        assert_eq!(
+            nextpost(schedule, utc(6, 1, 7, 0), amsterdam()),
+            Some(utc(6, 2, 7, 0))
+        );
+    }
+
+    #[test]
+    fn dailyskipsdaylightsavinggap() {
+        // 02:30 does not exist on 31 March in Amsterdam, the clocks go from 02:00 to 03:00
+        let schedule = DigestSchedule::Daily {
+            hour: 2,
+            minute: 30,
+        };
+        assert_eq!(
+            nextpost(schedule, utc(3, 30, 12, 0), amsterdam()),
+            Some(utc(4, 1, 0, 30))
+        );
+    }
+
+    #[test]
+    fn dailypostsoncedaylightsavingoverlap() {
+        // 02:30 happens twice on 27 October in Amsterdam, only the first one is used
+        let schedule = DigestSchedule::Daily {
+            hour: 2,
+            minute: 30,
+        };
+        assert_eq!(
+            nextpost(schedule, utc(10, 26, 12, 0), amsterdam()),
+            Some(utc(10, 27, 0, 30))
+        );
+        assert_eq!(
+            nextpost(schedule, utc(10, 27, 0, 30), amsterdam()),
+            Some(utc(10, 28, 1, 30))
+        );
+    }
+}
@@ -0,0 +1,229 @@
+#![cfg(feature = "database")]
+use crate::commands::pricecard::pricebuttons;
+use crate::dexscreener::Pair;
+use crate::formatting::{self, Locale};
+use crate::settings::commonfunctions::guildcoins;
+use crate::settings::dbstructs::MarketAlerts;
+use crate::{dexscreener, Error, DB};
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, Colour, CreateEmbed, CreateEmbedFooter, CreateMessage,
+};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Seconds between two samples of the volume and liquidity.
+const CHECKINTERVAL: u64 = 120;
+/// Samples older than a day are dropped, which is the longest window that can be chosen.
+const MAXWINDOW: Duration = Duration::from_secs(60 * 60 * 24);
+/// The window needs this many earlier samples before a spike or drop is reported.
+const MINSAMPLES: usize = 3;
+/// The shortest window in minutes that can hold enough samples, with one sample to spare for a late check.
+#[allow(clippy::cast_possible_truncation)]
+pub const MINWINDOW: u64 = (MINSAMPLES as u64 + 1) * CHECKINTERVAL / 60;
+/// Volume spikes are ignored when the average volume is below this amount of dollars, as tiny amounts spike easily.
+const MINVOLUME: f64 = 100.0;
+
+/// The volume and liquidity of a pair at one moment, `None` when Dexscreener did not return it.
+struct Sample {
+    at: Instant,
+    volume: Option<f64>,
+    liquidity: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum AlertKind {
+    VolumeSpike,
+    LiquidityDrop,
+}
+
+/// Watch the volume and liquidity of the tokens of all guilds with market alerts, and warn about spikes and drops.
+pub async fn marketalerts(ctx: serenity::Context) {
+    // The samples per lowercase pair address, as the best pair of a token can change between checks
+    let mut samples: HashMap<String, VecDeque<Sample>> = HashMap::new();
+    // When a warning was last posted per channel, token and kind, so each event is only reported once per window
+    let mut lastalerts: HashMap<(ChannelId, String, AlertKind), Instant> = HashMap::new();
+
+    loop {
+        match checkmarkets(&ctx, &mut samples, &mut lastalerts).await {
+            Ok(()) => {}
+            Err(alerterror) => println!("Could not check the market alerts: {alerterror}"),
+        }
+        tokio::time::sleep(Duration::from_secs(CHECKINTERVAL)).await;
+    }
+}
+
+async fn checkmarkets(
+    ctx: &serenity::Context,
+    samples: &mut HashMap<String, VecDeque<Sample>>,
+    lastalerts: &mut HashMap<(ChannelId, String, AlertKind), Instant>,
+) -> Result<(), Error> {
+    let configs: Vec<MarketAlerts> = DB.select("marketalerts").await?;
+    if configs.is_empty() {
+        samples.clear();
+        return Ok(());
+    }
+
+    let mut guildcoinlist = vec![];
+    let mut addresses: Vec<String> = vec![];
+    for config in &configs {
+        let coins = guildcoins(config.guildid).await?;
+        for coin in &coins {
+            if !addresses.contains(&coin.address) {
+                addresses.push(coin.address.clone());
+            }
+        }
+        guildcoinlist.push(coins);
+    }
+    let pairs = dexscreener::bestpairs(&addresses).await?;
+
+    let now = Instant::now();
+    for pair in pairs.values() {
+        let history = samples.entry(pair.pair_address.to_lowercase()).or_default();
+        while history
+            .front()
+            .is_some_and(|sample| now.duration_since(sample.at) > MAXWINDOW)
+        {
+            history.pop_front();
+        }
+        history.push_back(Sample {
+            at: now,
+            volume: pair.volume.as_ref().and_then(|x| x.m5),
+            liquidity: pair.liquidity.as_ref().and_then(|x| x.usd),
+        });
+    }
+    // Forget pairs that are not tracked anymore
+    samples.retain(|pairaddress, _| {
+        pairs
+            .values()
+            .any(|pair| pair.pair_address.eq_ignore_ascii_case(pairaddress))
+    });
+
+    for (config, coins) in configs.iter().zip(guildcoinlist) {
+        let window = Duration::from_secs(u64::try_from(config.window).unwrap_or(60) * 60);
+        for coin in coins {
+            let address = coin.address.to_lowercase();
+            let Some(pair) = pairs.get(&address) else {
+                continue;
+            };
+            let Some(history) = samples.get(&pair.pair_address.to_lowercase()) else {
+                continue;
+            };
+            for (kind, embed) in detect(config, &coin.name, pair, history, window) {
+                let key = (config.channelid, address.clone(), kind);
+                if lastalerts
+                    .get(&key)
+                    .is_some_and(|alerted| now.duration_since(*alerted) < window)
+                {
+                    continue;
+                }
+                lastalerts.insert(key, now);
+                if let Err(posterror) = config
+                    .channelid
+                    .send_message(
+                        ctx,
+                        CreateMessage::new()
+                            .embed(embed)
+                            .components(vec![pricebuttons(pair)]),
+                    )
+                    .await
+                {
+                    println!(
+                        "Could not post a market alert in {}: {posterror}",
+                        config.channelid
+                    );
+                }
+            }
+        }
+    }
+    lastalerts.retain(|_, alerted| now.duration_since(*alerted) < MAXWINDOW);
+    Ok(())
+}
+
+/// Compare the latest sample of a token against the earlier samples in the window.
+fn detect(
+    config: &MarketAlerts,
+    name: &str,
+    pair: &Pair,
+    history: &VecDeque<Sample>,
+    window: Duration,
+) -> Vec<(AlertKind, CreateEmbed)> {
+    let Some(latest) = history.back() else {
+        return vec![];
+    };
+    let earlier: Vec<&Sample> = history
+        .iter()
+        .rev()
+        .skip(1)
+        .take_while(|sample| latest.at.duration_since(sample.at) <= window)
+        .collect();
+    let locale = Locale::from_env();
+    let mut alerts = vec![];
+
+    // Samples without a value are skipped, counting them as 0 would look like a spike or a drop
+    let volumes: Vec<f64> = earlier.iter().filter_map(|sample| sample.volume).collect();
+    #[allow(clippy::cast_precision_loss)]
+    let averagevolume = volumes.iter().sum::<f64>() / volumes.len().max(1) as f64;
+    if let Some(latestvolume) = latest.volume.filter(|volume| {
+        volumes.len() >= MINSAMPLES
+            && averagevolume >= MINVOLUME
+            && *volume >= averagevolume * config.volumefactor
+    }) {
+        alerts.push((
+            AlertKind::VolumeSpike,
+            alertembed(pair, Colour::from_rgb(255, 165, 0))
+                .title(format!("📊 Volume spike: {name}"))
+                .description(format!(
+                    "The 5 minute volume is **{:.1}x** the average of the last {} minutes",
+                    latestvolume / averagevolume,
+                    config.window
+                ))
+                .field(
+                    "Volume (5m)",
+                    formatting::compactusd(latestvolume, locale),
+                    true,
+                )
+                .field(
+                    "Average",
+                    formatting::compactusd(averagevolume, locale),
+                    true,
+                ),
+        ));
+    }
+
+    let liquidities: Vec<f64> = earlier
+        .iter()
+        .filter_map(|sample| sample.liquidity)
+        .collect();
+    let highestliquidity = liquidities.iter().copied().fold(0.0, f64::max);
+    if let Some(latestliquidity) = latest
+        .liquidity
+        .filter(|_| liquidities.len() >= MINSAMPLES && highestliquidity > 0.0)
+    {
+        let drop = (highestliquidity - latestliquidity) / highestliquidity * 100.0;
+        if drop >= config.liquiditydrop {
+            alerts.push((
+                AlertKind::LiquidityDrop,
+                alertembed(pair, Colour::from_rgb(255, 0, 0))
+                    .title(format!("🚨 Liquidity drop: {name}"))
+                    .description(format!(
+                        "The liquidity dropped **{}** within the last {} minutes. Liquidity might have been pulled.",
+                        formatting::percentage(-drop, locale),
+                        config.window
+                    ))
+                    .field("Liquidity", formatting::compactusd(latestliquidity, locale), true)
+                    .field("Highest", formatting::compactusd(highestliquidity, locale), true),
+            ));
+        }
+    }
+    alerts
+}
+
+fn alertembed(pair: &Pair, colour: Colour) -> CreateEmbed {
+    CreateEmbed::default()
+        .url(pair.url.clone())
+        .colour(colour)
+        .footer(CreateEmbedFooter::new(
+            "All rights reserved to Dexscreener.com",
+        ))
+        .timestamp(serenity::Timestamp::now())
+}
@@ -0,0 +1,7 @@
+//! Tasks that run in the background for as long as the bot is running.
+pub mod boostfeed;
+pub mod digests;
+pub mod marketalerts;
+pub mod newpairs;
+pub mod statchannels;
+pub mod ticker;
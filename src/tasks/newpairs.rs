@@ -0,0 +1,142 @@
+#![cfg(feature = "database")]
+use crate::commands::pricecard::{newpaircard, pricebuttons};
+use crate::dexscreener::Pair;
+use crate::settings::dbstructs::{PairFeed, SeenPair};
+use crate::{dexscreener, Error, DB};
+use chrono::Utc;
+use poise::serenity_prelude::{self as serenity, ChannelId, CreateMessage};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Seconds between two checks for new pairs.
+const CHECKINTERVAL: u64 = 60;
+/// Seen pairs are forgotten after 2 days, which is older than the maximum age a feed accepts.
+/// A pair is only checked until it is older than the maximum age of all feeds, so it is not posted again after that.
+const SEENRETENTION: i64 = 60 * 60 * 48;
+
+/// Post newly created pairs in the new pair feed channels of all guilds.
+///
+/// Dexscreener has no public list of all new pools, so the pairs of tokens that recently got a profile or boost are checked.
+pub async fn newpairs(ctx: serenity::Context) {
+    loop {
+        match postnewpairs(&ctx).await {
+            Ok(()) => {}
+            Err(newpairerror) => println!("Could not update the new pair feeds: {newpairerror}"),
+        }
+        tokio::time::sleep(Duration::from_secs(CHECKINTERVAL)).await;
+    }
+}
+
+async fn postnewpairs(ctx: &serenity::Context) -> Result<(), Error> {
+    let feeds: Vec<PairFeed> = DB.select("pairfeeds").await?;
+    if feeds.is_empty() {
+        return Ok(());
+    }
+    let now = Utc::now().timestamp();
+    DB.query("DELETE seenpairs WHERE seenat < $before")
+        .bind(("before", now - SEENRETENTION))
+        .await?;
+
+    // Only fetch the pairs of tokens on chains that have a feed
+    let chains: HashSet<&str> = feeds.iter().map(|feed| feed.chain.as_str()).collect();
+    let mut addresses: Vec<String> = vec![];
+    let mut candidates = dexscreener::latestprofiles().await?;
+    candidates.extend(dexscreener::latestboosts().await?);
+    for candidate in candidates {
+        if chains.contains(candidate.chain_id.as_str())
+            && !addresses.contains(&candidate.token_address)
+        {
+            addresses.push(candidate.token_address);
+        }
+    }
+    let pairs = dexscreener::tokenspairs(&addresses).await?;
+
+    let seenpairs: Vec<SeenPair> = DB.select("seenpairs").await?;
+    // When nothing was seen before, only remember the current pairs instead of flooding the feeds
+    let firstrun = seenpairs.is_empty();
+    let mut seen: HashSet<(String, String, Option<ChannelId>)> = seenpairs
+        .into_iter()
+        .map(|seenpair| (seenpair.chainid, seenpair.pairaddress, seenpair.channelid))
+        .collect();
+    let maxage = feeds.iter().map(|feed| feed.maxage).max().unwrap_or(0);
+
+    for pair in pairs {
+        if seen.contains(&(pair.chain_id.clone(), pair.pair_address.clone(), None)) {
+            continue;
+        }
+        // Pairs without a creation time can not be checked against the maximum age
+        let Some(createdat) = pair.pair_created_at else {
+            continue;
+        };
+        // Pairs often get their liquidity a few minutes after they are created, so a pair is only skipped
+        // in all feeds once it is too old for all of them, until then it is checked again every time
+        if firstrun || now - createdat / 1000 > maxage * 60 {
+            markseen(&mut seen, &pair, None, now).await?;
+            continue;
+        }
+
+        for feed in feeds.iter().filter(|feed| matchesfeed(feed, &pair, now)) {
+            let key = (
+                pair.chain_id.clone(),
+                pair.pair_address.clone(),
+                Some(feed.channelid),
+            );
+            if seen.contains(&key) {
+                continue;
+            }
+            markseen(&mut seen, &pair, Some(feed.channelid), now).await?;
+            if let Err(posterror) = feed
+                .channelid
+                .send_message(
+                    ctx,
+                    CreateMessage::new()
+                        .embed(newpaircard(&pair))
+                        .components(vec![pricebuttons(&pair)]),
+                )
+                .await
+            {
+                println!(
+                    "Could not post a new pair in {}: {posterror}",
+                    feed.channelid
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remember that a pair was posted in a feed, or skipped in all feeds when `channelid` is `None`.
+async fn markseen(
+    seen: &mut HashSet<(String, String, Option<ChannelId>)>,
+    pair: &Pair,
+    channelid: Option<ChannelId>,
+    now: i64,
+) -> Result<(), Error> {
+    let id = match channelid {
+        Some(channelid) => format!("{}:{}:{channelid}", pair.chain_id, pair.pair_address),
+        None => format!("{}:{}", pair.chain_id, pair.pair_address),
+    };
+    let _: Option<SeenPair> = DB
+        .create(("seenpairs", id))
+        .content(SeenPair {
+            chainid: pair.chain_id.clone(),
+            pairaddress: pair.pair_address.clone(),
+            seenat: now,
+            channelid,
+        })
+        .await?;
+    seen.insert((pair.chain_id.clone(), pair.pair_address.clone(), channelid));
+    Ok(())
+}
+
+/// Check if a pair is on the chain and DEX of a feed, has enough liquidity and is not too old.
+fn matchesfeed(feed: &PairFeed, pair: &Pair, now: i64) -> bool {
+    let liquidity = pair.liquidity.as_ref().and_then(|x| x.usd).unwrap_or(0.0);
+    let Some(createdat) = pair.pair_created_at else {
+        return false;
+    };
+    feed.chain == pair.chain_id
+        && (feed.dex.is_none() || feed.dex.as_ref() == Some(&pair.dex_id))
+        && liquidity >= feed.minliquidity
+        && now - createdat / 1000 <= feed.maxage * 60
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "database")]
+use crate::dexscreener::Pair;
+use crate::formatting::{self, Locale};
+use crate::settings::dbstructs::{StatChannel, StatDisplay};
+use crate::{dexscreener, Error, DB};
+use poise::serenity_prelude::{self as serenity, ChannelId, EditChannel};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Seconds between two checks whether a stat channel needs a new name.
+const CHECKINTERVAL: u64 = 60;
+/// Discord allows a channel to be renamed twice per 10 minutes.
+const MAXRENAMES: u64 = 2;
+const RENAMEWINDOW: Duration = Duration::from_secs(60 * 10);
+/// Renames are spread evenly over the window, instead of two renames right after each other and then none for minutes.
+const RENAMESPACING: Duration = Duration::from_secs(RENAMEWINDOW.as_secs() / MAXRENAMES);
+
+/// Rename the stat channels of all guilds to show the current price or market cap of their token.
+pub async fn statchannels(ctx: serenity::Context) {
+    // The name and time of the last rename per channel, to stay within the rate limit
+    let mut renames: HashMap<ChannelId, (String, Instant)> = HashMap::new();
+
+    loop {
+        match updatestatchannels(&ctx, &mut renames).await {
+            Ok(()) => {}
+            Err(statchannelerror) => {
+                println!("Could not update the stat channels: {statchannelerror}");
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(CHECKINTERVAL)).await;
+    }
+}
+
+async fn updatestatchannels(
+    ctx: &serenity::Context,
+    renames: &mut HashMap<ChannelId, (String, Instant)>,
+) -> Result<(), Error> {
+    let statchannels: Vec<StatChannel> = DB.select("statchannels").await?;
+    let locale = Locale::from_env();
+
+    for statchannel in statchannels {
+        if renames
+            .get(&statchannel.channelid)
+            .is_some_and(|(_, renamed)| renamed.elapsed() < RENAMESPACING)
+        {
+            continue;
+        }
+
+        let pair = match dexscreener::bestpair(&statchannel.address).await {
+            Ok(Some(pair)) => pair,
+            Ok(None) => continue,
+            Err(fetcherror) => {
+                println!(
+                    "Could not fetch {} for a stat channel: {fetcherror}",
+                    statchannel.name
+                );
+                continue;
+            }
+        };
+        let Some(channelname) = channelname(&statchannel, &pair, locale) else {
+            continue;
+        };
+        if renames
+            .get(&statchannel.channelid)
+            .is_some_and(|(lastname, _)| *lastname == channelname)
+        {
+            continue;
+        }
+
+        match statchannel
+            .channelid
+            .edit(ctx, EditChannel::new().name(&channelname))
+            .await
+        {
+            Ok(_) => {
+                renames.insert(statchannel.channelid, (channelname, Instant::now()));
+            }
+            Err(renameerror) => println!(
+                "Could not rename stat channel {}: {renameerror}",
+                statchannel.channelid
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// The name of a stat channel: `BTC: $67,120` or `BTC MC: $1.23B`.
+pub fn channelname(statchannel: &StatChannel, pair: &Pair, locale: Locale) -> Option<String> {
+    match statchannel.display {
+        StatDisplay::Price => Some(format!(
+            "{}: {}",
+            statchannel.name,
+            formatting::pricestr(pair.price_usd.as_ref()?, locale)
+        )),
+        StatDisplay::MarketCap => Some(format!(
+            "{} MC: {}",
+            statchannel.name,
+            formatting::compactusd(pair.market_cap.or(pair.fdv)?, locale)
+        )),
+    }
+}
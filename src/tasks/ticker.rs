@@ -0,0 +1,126 @@
+#![cfg(feature = "database")]
+use crate::formatting::{self, Locale};
+use crate::settings::dbstructs::Ticker;
+use crate::{dexscreener, Error, DB};
+use poise::serenity_prelude::{self as serenity, ActivityData, GuildId};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Seconds between two updates if `TICKER_INTERVAL` is not set.
+const DEFAULTINTERVAL: u64 = 300;
+/// Nickname edits are rate limited by Discord, so never update a guild more often than this.
+const MININTERVAL: u64 = 60;
+/// Discord does not allow longer nicknames.
+const MAXNICKNAMELENGTH: usize = 32;
+
+/// Keep the nickname of the bot up to date with the price of the token chosen in each guild.
+/// The presence of the bot is shared over all guilds, so it rotates through the tickers.
+pub async fn ticker(ctx: serenity::Context) {
+    let interval = match std::env::var("TICKER_INTERVAL") {
+        Ok(val) => val.parse::<u64>().unwrap_or(DEFAULTINTERVAL),
+        Err(_) => DEFAULTINTERVAL,
+    }
+    .max(MININTERVAL);
+    // The nickname and time of the last edit per guild, so nothing is edited when the price did not change
+    let mut lastnicknames: HashMap<GuildId, (String, Instant)> = HashMap::new();
+    let mut presenceindex: usize = 0;
+    // Whether the presence shows a ticker, so it is only cleared once when all tickers are turned off
+    let mut showingpresence = false;
+
+    loop {
+        match updatetickers(
+            &ctx,
+            &mut lastnicknames,
+            &mut presenceindex,
+            &mut showingpresence,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err(tickererror) => println!("Could not update the tickers: {tickererror}"),
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+async fn updatetickers(
+    ctx: &serenity::Context,
+    lastnicknames: &mut HashMap<GuildId, (String, Instant)>,
+    presenceindex: &mut usize,
+    showingpresence: &mut bool,
+) -> Result<(), Error> {
+    let tickers: Vec<Ticker> = DB.select("ticker").await?;
+    let tickers: Vec<Ticker> = tickers.into_iter().filter(|x| x.enabled).collect();
+    // Turning a ticker off resets the nickname, so forget it to set the nickname again when it is turned back on
+    lastnicknames.retain(|guildid, _| tickers.iter().any(|ticker| ticker.guildid == *guildid));
+    if tickers.is_empty() {
+        if *showingpresence {
+            ctx.set_activity(None);
+            *showingpresence = false;
+        }
+        return Ok(());
+    }
+    let locale = Locale::from_env();
+
+    let mut presences = vec![];
+    for ticker in &tickers {
+        let pair = match dexscreener::bestpair(&ticker.address).await {
+            Ok(Some(pair)) => pair,
+            Ok(None) => continue,
+            Err(fetcherror) => {
+                println!(
+                    "Could not fetch {} for the ticker: {fetcherror}",
+                    ticker.name
+                );
+                continue;
+            }
+        };
+        let Some(price) = &pair.price_usd else {
+            continue;
+        };
+        let pricechange = pair
+            .price_change
+            .as_ref()
+            .and_then(|x| x.h24)
+            .unwrap_or(0.0);
+        let arrow = if pricechange >= 0.0 { "↗" } else { "↘" };
+        let nickname: String = format!(
+            "{} {} {arrow}",
+            ticker.name,
+            formatting::pricestr(price, locale)
+        )
+        .chars()
+        .take(MAXNICKNAMELENGTH)
+        .collect();
+        presences.push(format!(
+            "{nickname} {}",
+            formatting::percentage(pricechange, locale)
+        ));
+
+        let unchanged = lastnicknames
+            .get(&ticker.guildid)
+            .is_some_and(|(last, at)| {
+                *last == nickname || at.elapsed() < Duration::from_secs(MININTERVAL)
+            });
+        if unchanged {
+            continue;
+        }
+        match ticker.guildid.edit_nickname(ctx, Some(&nickname)).await {
+            Ok(()) => {
+                lastnicknames.insert(ticker.guildid, (nickname, Instant::now()));
+            }
+            Err(nicknameerror) => println!(
+                "Could not change the nickname in {}: {nicknameerror}",
+                ticker.guildid
+            ),
+        }
+    }
+
+    if !presences.is_empty() {
+        let presence = &presences[*presenceindex % presences.len()];
+        ctx.set_activity(Some(ActivityData::watching(presence)));
+        *presenceindex = presenceindex.wrapping_add(1);
+        *showingpresence = true;
+    }
+    Ok(())
+}
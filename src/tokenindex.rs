@@ -0,0 +1,396 @@
+//! In-memory index of the known tokens, used to suggest tokens in `/price` without reading the database on every keystroke.
+//!
+//! Tokens are matched case-insensitively on their symbol, name, alias and the start of their address.
+//! Exact and prefix matches rank above fuzzy matches. Among equally good matches, verified and often used tokens rank higher.
+//! How often tokens are used is only kept in memory, so it starts over when the bot restarts.
+#![allow(clippy::module_name_repetitions)]
+use crate::tokenstore::{store, StoredToken, TokenStore};
+use crate::{dexscreener, Context, Error};
+use once_cell::sync::Lazy;
+use poise::serenity_prelude::{AutocompleteChoice, GuildId};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Discord shows at most 25 autocomplete choices.
+const MAXCHOICES: usize = 25;
+/// Discord allows at most 100 characters in the label of a choice.
+const MAXLABEL: usize = 100;
+/// Seconds between two refreshes of the index.
+const REFRESHINTERVAL: u64 = 300;
+/// Addresses are only matched from this many characters onwards, as every EVM address starts with `0x`.
+const MINADDRESSQUERY: usize = 3;
+
+/// A token that can be chosen in `/price`.
+/// - `alias`: the name used for the token in the bot, which can differ from its symbol
+/// - `guildid`: the guild the token belongs to, `None` for tokens that are available in all guilds
+#[derive(Debug, Clone)]
+pub struct IndexedToken {
+    pub symbol: String,
+    pub name: String,
+    pub alias: String,
+    pub address: String,
+    pub chain: Option<String>,
+    pub guildid: Option<GuildId>,
+    pub verified: bool,
+}
+
+/// The symbol, name and chain of a token as found on Dexscreener.
+#[derive(Debug, Clone)]
+struct TokenDetails {
+    symbol: String,
+    name: String,
+    chain: String,
+}
+
+#[derive(Default)]
+struct TokenIndex {
+    tokens: Vec<IndexedToken>,
+    /// Details per lowercase address, kept between refreshes so they are only fetched once.
+    /// `None` for addresses Dexscreener has no pair for, so these are not fetched again on every refresh
+    details: HashMap<String, Option<TokenDetails>>,
+    /// How often a token was priced per guild and lowercase address, since the bot started
+    usage: HashMap<(Option<GuildId>, String), u64>,
+}
+
+static INDEX: Lazy<Mutex<TokenIndex>> = Lazy::new(|| Mutex::new(TokenIndex::default()));
+
+/// Lock the index, a panic while holding the lock can not leave the index in a broken state.
+fn index() -> MutexGuard<'static, TokenIndex> {
+    match INDEX.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Keep the index up to date with tokens that are added or changed outside of the bot.
+pub async fn keepfresh() {
+    loop {
+        if let Err(refresherror) = refresh().await {
+            println!("Could not refresh the token index: {refresherror}");
+        }
+        tokio::time::sleep(Duration::from_secs(REFRESHINTERVAL)).await;
+    }
+}
+
+/// Rebuild the index from the stored tokens. Call this after adding or changing tokens.
+/// Missing details are fetched from Dexscreener, tokens are still indexed when Dexscreener can not be reached.
+pub async fn refresh() -> Result<(), Error> {
+    let mut tokens = storedtokens().await?;
+
+    let missing: Vec<String> = {
+        let index = index();
+        tokens
+            .iter()
+            .map(|token| token.address.clone())
+            .filter(|address| !index.details.contains_key(&address.to_lowercase()))
+            .collect()
+    };
+    // Addresses that are not returned as base token are remembered as without details, until the bot restarts
+    let fetched = if missing.is_empty() {
+        None
+    } else {
+        match dexscreener::bestpairs(&missing).await {
+            Ok(pairs) => Some(pairs),
+            Err(fetcherror) => {
+                println!("Could not fetch the details of the indexed tokens: {fetcherror}");
+                None
+            }
+        }
+    };
+
+    let mut index = index();
+    if let Some(mut fetched) = fetched {
+        for address in missing {
+            let address = address.to_lowercase();
+            let details = fetched.remove(&address).map(|pair| TokenDetails {
+                symbol: pair.base_token.symbol,
+                name: pair.base_token.name,
+                chain: pair.chain_id,
+            });
+            index.details.insert(address, details);
+        }
+    }
+    for token in &mut tokens {
+        if let Some(Some(details)) = index.details.get(&token.address.to_lowercase()) {
+            token.symbol.clone_from(&details.symbol);
+            token.name.clone_from(&details.name);
+            token.chain = Some(details.chain.clone());
+        }
+    }
+    index.tokens = tokens;
+    Ok(())
+}
+
+/// Refresh the index after tokens were added or changed.
+/// The change itself already succeeded, so a failed refresh is only logged and picked up by the next periodic refresh.
+pub async fn tryrefresh() {
+    if let Err(refresherror) = refresh().await {
+        println!("Could not refresh the token index: {refresherror}");
+    }
+}
+
+/// The stored tokens, without details from Dexscreener.
+async fn storedtokens() -> Result<Vec<IndexedToken>, Error> {
+    Ok(store()
+        .list()
+        .await?
+        .into_iter()
+        .map(IndexedToken::from)
+        .collect())
+}
+
+impl From<StoredToken> for IndexedToken {
+    /// Index a stored token, its name is used as symbol and name until the details are fetched.
+    fn from(token: StoredToken) -> Self {
+        IndexedToken {
+            symbol: token.name.clone(),
+            name: token.name.clone(),
+            alias: token.name,
+            address: token.address,
+            chain: token.chain,
+            guildid: if token.global { None } else { token.guildid },
+            verified: token.verified,
+        }
+    }
+}
+
+/// Remember that a token was priced in a guild, so it ranks higher in that guild.
+pub fn recordusage(guildid: Option<GuildId>, address: &str) {
+    *index()
+        .usage
+        .entry((guildid, address.to_lowercase()))
+        .or_insert(0) += 1;
+}
+
+/// The symbol and name of a token on Dexscreener, if they were fetched already.
+#[cfg(feature = "database")]
+pub fn details(address: &str) -> Option<(String, String)> {
+    index()
+        .details
+        .get(&address.to_lowercase())?
+        .as_ref()
+        .map(|details| (details.symbol.clone(), details.name.clone()))
+}
+
+/// Find a token by its exact address, alias or symbol.
+pub fn find(query: &str, guildid: Option<GuildId>) -> Option<IndexedToken> {
+    findin(&index().tokens, query, guildid)
+}
+
+/// Find a token in a list by its exact address, alias or symbol, in that order.
+pub fn findin(
+    tokens: &[IndexedToken],
+    query: &str,
+    guildid: Option<GuildId>,
+) -> Option<IndexedToken> {
+    let query = query.trim();
+    let visible = || tokens.iter().filter(|token| isvisible(token, guildid));
+    visible()
+        .find(|token| token.address.eq_ignore_ascii_case(query))
+        .or_else(|| visible().find(|token| token.alias.eq_ignore_ascii_case(query)))
+        .or_else(|| visible().find(|token| token.symbol.eq_ignore_ascii_case(query)))
+        .cloned()
+}
+
+/// Find the tokens that match a partial query, best matches first.
+pub fn search(query: &str, guildid: Option<GuildId>) -> Vec<IndexedToken> {
+    let index = index();
+    rank(&index.tokens, query, guildid, |token| {
+        index
+            .usage
+            .get(&(guildid, token.address.to_lowercase()))
+            .copied()
+            .unwrap_or(0)
+    })
+}
+
+/// Rank the tokens in a list that match a partial query, best matches first.
+/// `usage` tells how often a token was priced in the guild.
+pub fn rank(
+    tokens: &[IndexedToken],
+    query: &str,
+    guildid: Option<GuildId>,
+    usage: impl Fn(&IndexedToken) -> u64,
+) -> Vec<IndexedToken> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<(u64, u64, &IndexedToken)> = tokens
+        .iter()
+        .filter(|token| isvisible(token, guildid))
+        .filter_map(|token| {
+            let score = tokenscore(&query, token)?;
+            // Popularity and verification only order equally good matches, so they never push a poor match above a good one
+            let bonus = usage(token).min(30) + if token.verified { 15 } else { 0 };
+            Some((score, bonus, token))
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(b.1.cmp(&a.1))
+            .then(a.2.alias.cmp(&b.2.alias))
+    });
+    matches
+        .into_iter()
+        .take(MAXCHOICES)
+        .map(|(_, _, token)| token.clone())
+        .collect()
+}
+
+/// Suggest tokens for a `/price` option. The label is `SYMBOL — Name (chain)` and the alias, the value is the address.
+#[allow(clippy::unused_async)]
+pub async fn autocomplete(ctx: Context<'_>, partial: &str) -> Vec<AutocompleteChoice> {
+    store()
+        .search(partial, ctx.guild_id())
+        .iter()
+        .map(|token| AutocompleteChoice::new(label(token), token.address.clone()))
+        .collect()
+}
+
+/// The label of a token in autocomplete, e.g. `✅ OP — Optimism (optimism)`.
+/// The alias is added when the token is stored under another name than its symbol, e.g. `OP — Optimism (optimism) · alias Optimism`.
+pub fn label(token: &IndexedToken) -> String {
+    let mut label = format!("{} — {}", token.symbol, token.name);
+    if token.verified {
+        label = format!("✅ {label}");
+    }
+    if let Some(chain) = &token.chain {
+        label = format!("{label} ({chain})");
+    }
+    if !token.alias.eq_ignore_ascii_case(&token.symbol) {
+        label = format!("{label} · alias {}", token.alias);
+    }
+    label.chars().take(MAXLABEL).collect()
+}
+
+fn isvisible(token: &IndexedToken, guildid: Option<GuildId>) -> bool {
+    token.guildid.is_none() || token.guildid == guildid
+}
+
+/// Score how well a token matches a lowercase query, `None` if it does not match at all.
+fn tokenscore(query: &str, token: &IndexedToken) -> Option<u64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let address = token.address.to_lowercase();
+    let addressscore = (query.len() >= MINADDRESSQUERY && address.starts_with(query)).then_some(70);
+    [
+        textscore(query, &token.alias),
+        textscore(query, &token.symbol),
+        // A match on the full name is a bit less likely to be what was meant
+        textscore(query, &token.name).map(|score| score * 4 / 5),
+        addressscore,
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+}
+
+/// Score a text: exact 100, prefix 80, substring 50, or up to 30 when the letters appear in order.
+fn textscore(query: &str, text: &str) -> Option<u64> {
+    let text = text.to_lowercase();
+    if text == query {
+        Some(100)
+    } else if text.starts_with(query) {
+        Some(80)
+    } else if text.contains(query) {
+        Some(50)
+    } else {
+        fuzzyscore(query, &text)
+    }
+}
+
+/// Check if all characters of the query appear in the text in order, fewer skipped characters score higher.
+fn fuzzyscore(query: &str, text: &str) -> Option<u64> {
+    let mut textchars = text.chars();
+    let mut skipped: u64 = 0;
+    for querychar in query.chars() {
+        loop {
+            let textchar = textchars.next()?;
+            if textchar == querychar {
+                break;
+            }
+            skipped += 1;
+        }
+    }
+    Some(30u64.saturating_sub(skipped * 3).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(alias: &str, address: &str, verified: bool) -> IndexedToken {
+        IndexedToken {
+            symbol: alias.to_string(),
+            name: alias.to_string(),
+            alias: alias.to_string(),
+            address: address.to_string(),
+            chain: None,
+            guildid: None,
+            verified,
+        }
+    }
+
+    fn aliases(tokens: Vec<IndexedToken>) -> Vec<String> {
+        tokens.into_iter().map(|token| token.alias).collect()
+    }
+
+    #[test]
+    fn bonusdoesnotbeatbettermatch() {
+        let tokens = vec![
+            token("WOPX", "0x01", true),
+            token("OPX", "0x02", false),
+            token("OPXL", "0x03", false),
+        ];
+        // The verified, often used substring match stays below the exact and prefix matches
+        let ranked = rank(&tokens, "opx", None, |token| {
+            if token.alias == "WOPX" {
+                1000
+            } else {
+                0
+            }
+        });
+        assert_eq!(aliases(ranked), vec!["OPX", "OPXL", "WOPX"]);
+    }
+
+    #[test]
+    fn bonusorderssamematch() {
+        let tokens = vec![
+            token("OPA", "0x01", false),
+            token("OPB", "0x02", true),
+            token("OPC", "0x03", false),
+        ];
+        let ranked = rank(&tokens, "op", None, |token| {
+            if token.alias == "OPC" {
+                20
+            } else {
+                0
+            }
+        });
+        assert_eq!(aliases(ranked), vec!["OPC", "OPB", "OPA"]);
+    }
+
+    #[test]
+    fn labelshowssymbolnameandalias() {
+        let mut optimism = token("Optimism", "0x01", true);
+        optimism.symbol = "OP".to_string();
+        optimism.chain = Some("optimism".to_string());
+        assert_eq!(
+            label(&optimism),
+            "✅ OP — Optimism (optimism) · alias Optimism"
+        );
+        optimism.alias = "op".to_string();
+        assert_eq!(label(&optimism), "✅ OP — Optimism (optimism)");
+        optimism.name = "x".repeat(200);
+        assert_eq!(label(&optimism).chars().count(), MAXLABEL);
+    }
+
+    #[test]
+    fn findprefersaddressthenalias() {
+        let tokens = vec![token("0x02", "0x01", false), token("OTHER", "0x02", false)];
+        assert_eq!(
+            findin(&tokens, "0x02", None).map(|token| token.alias),
+            Some("OTHER".to_string())
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! The token list of the build without a database, read from a JSON file.
+//!
+//! The file is set with `TOKEN_LIST` (default `tokens.json`) and contains the tokens that can be chosen in `/price`:
+//! ```json
+//! [
+//!     { "name": "BTC", "address": "0xC0BC84e95864BdfDCd1CCFB8A3AA522E79Ca1410" },
+//!     { "name": "OP", "address": "0x4200000000000000000000000000000000000042" }
+//! ]
+//! ```
+//! Changes to the file are picked up while the bot is running. A file with mistakes is refused and the previous list is kept.
+#![cfg(not(feature = "database"))]
+use crate::{dexscreener, tokenindex, Error};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+/// Seconds between two checks whether the file changed.
+const CHECKINTERVAL: u64 = 10;
+/// The tokens used when there is no token list file.
+const DEFAULTTOKENS: [(&str, &str); 4] = [
+    ("BTC", "0xC0BC84e95864BdfDCd1CCFB8A3AA522E79Ca1410"),
+    ("OPENX", "0xc3864f98f2a61A7cAeb95b039D031b4E2f55e0e9"),
+    ("OP", "0x4200000000000000000000000000000000000042"),
+    ("ETH", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListedToken {
+    pub name: String,
+    pub address: String,
+}
+
+static TOKENS: Lazy<Mutex<Vec<ListedToken>>> = Lazy::new(|| Mutex::new(defaulttokens()));
+/// Held while a token is added and the file is written, separate from the list so reading it is not blocked.
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Lock the token list, a panic while holding the lock can not leave the list in a broken state.
+fn tokenlist() -> MutexGuard<'static, Vec<ListedToken>> {
+    match TOKENS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// The tokens that can be chosen in `/price`.
+pub fn tokens() -> Vec<ListedToken> {
+    tokenlist().clone()
+}
+
+fn defaulttokens() -> Vec<ListedToken> {
+    DEFAULTTOKENS
+        .iter()
+        .map(|(name, address)| ListedToken {
+            name: (*name).to_string(),
+            address: (*address).to_string(),
+        })
+        .collect()
+}
+
+fn path() -> String {
+    std::env::var("TOKEN_LIST").unwrap_or("tokens.json".to_string())
+}
+
+/// Load the token list on startup. Stops the bot when the file has mistakes, so they are noticed right away.
+pub fn load() {
+    let path = path();
+    if !std::path::Path::new(&path).exists() {
+        println!("There is no token list at {path}, using the default tokens");
+        return;
+    }
+    match readfile(&path) {
+        Ok(tokens) => {
+            println!("Loaded {} tokens from {path}", tokens.len());
+            *tokenlist() = tokens;
+        }
+        Err(listerror) => panic!("The token list at {path} is not valid: {listerror}"),
+    }
+}
+
+/// Reload the token list whenever the file changes.
+pub async fn watch() {
+    let path = path();
+    let modified = |path: &str| std::fs::metadata(path).and_then(|x| x.modified()).ok();
+    let mut lastmodified: Option<SystemTime> = modified(&path);
+    loop {
+        tokio::time::sleep(Duration::from_secs(CHECKINTERVAL)).await;
+        let currentmodified = modified(&path);
+        if currentmodified == lastmodified {
+            continue;
+        }
+        lastmodified = currentmodified;
+        if currentmodified.is_none() {
+            println!("The token list at {path} was removed, keeping the current tokens");
+            continue;
+        }
+        match readfile(&path) {
+            Ok(tokens) => {
+                println!("Reloaded {} tokens from {path}", tokens.len());
+                *tokenlist() = tokens;
+                tokenindex::tryrefresh().await;
+            }
+            Err(listerror) => {
+                println!("The token list at {path} is not valid, keeping the current tokens: {listerror}");
+            }
+        }
+    }
+}
+
+/// Add a token to the list and write the list to the file.
+/// The file is written on a blocking thread, so the list stays available while writing.
+pub async fn insert(token: ListedToken) -> Result<(), Error> {
+    tokio::task::spawn_blocking(move || {
+        // Only one insert writes at a time, so an insert can not overwrite the token of another
+        let _writing = match WRITING.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let newtokens = {
+            let mut newtokens = tokenlist().clone();
+            newtokens.push(token);
+            newtokens
+        };
+        validate(&newtokens)?;
+        std::fs::write(path(), serde_json::to_string_pretty(&newtokens)?)?;
+        *tokenlist() = newtokens;
+        Ok(())
+    })
+    .await?
+}
+
+/// Read and validate a token list file.
+fn readfile(path: &str) -> Result<Vec<ListedToken>, Error> {
+    let content = std::fs::read_to_string(path)?;
+    let tokens: Vec<ListedToken> = serde_json::from_str(&content)?;
+    validate(&tokens)?;
+    Ok(tokens)
+}
+
+/// Check that every token has a unique name and a valid address.
+fn validate(tokens: &[ListedToken]) -> Result<(), Error> {
+    let mut names = HashSet::new();
+    for token in tokens {
+        if token.name.trim().is_empty() {
+            return Err(format!("{} has no name", token.address).into());
+        }
+        if !names.insert(token.name.to_lowercase()) {
+            return Err(format!("{} is in the list more than once", token.name).into());
+        }
+        if !dexscreener::isevmaddress(&token.address)
+            && !dexscreener::issolanaaddress(&token.address)
+        {
+            return Err(format!(
+                "{} of {} is not an EVM or Solana address",
+                token.address, token.name
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(name: &str, address: &str) -> ListedToken {
+        ListedToken {
+            name: name.to_string(),
+            address: address.to_string(),
+        }
+    }
+
+    #[test]
+    fn defaulttokensarevalid() {
+        assert!(validate(&defaulttokens()).is_ok());
+    }
+
+    #[test]
+    fn duplicatenames() {
+        let mut tokens = defaulttokens();
+        tokens.push(listed("btc", "0x4200000000000000000000000000000000000006"));
+        assert!(validate(&tokens).is_err());
+    }
+
+    #[test]
+    fn badaddresses() {
+        for address in [
+            "",
+            "0x42",
+            "0x420000000000000000000000000000000000004z",
+            "not an address",
+        ] {
+            assert!(validate(&[listed("X", address)]).is_err(), "{address}");
+        }
+        assert!(validate(&[listed("SOL", "So11111111111111111111111111111111111111112")]).is_ok());
+    }
+
+    #[test]
+    fn emptynames() {
+        for name in ["", "   "] {
+            assert!(
+                validate(&[listed(name, "0x4200000000000000000000000000000000000042")]).is_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Storage of the tokens that can be chosen in `/price`.
+//!
+//! The database build stores tokens in `SurrealDB`, the build without a database reads them from the token list file.
+//! Commands use [`store`] and the [`TokenStore`] trait, so they work the same in both builds.
+//! Tests use a `MemoryStore`, which keeps its tokens in memory.
+#![allow(clippy::module_name_repetitions)]
+use crate::tokenindex::{self, IndexedToken};
+use crate::Error;
+use poise::serenity_prelude::GuildId;
+
+/// A token that can be chosen in `/price`.
+/// - `guildid`: the guild that added the token, `None` for tokens from the token list file
+/// - `global`: available in all guilds instead of only the guild that added it
+/// - `chain`: the chain on Dexscreener, `None` if it is not known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredToken {
+    pub name: String,
+    pub address: String,
+    pub guildid: Option<GuildId>,
+    pub global: bool,
+    pub verified: bool,
+    pub chain: Option<String>,
+}
+
+impl StoredToken {
+    /// Check if the token can be used in a guild.
+    pub fn isvisible(&self, guildid: Option<GuildId>) -> bool {
+        self.global || (self.guildid.is_some() && self.guildid == guildid)
+    }
+}
+
+#[allow(async_fn_in_trait)]
+pub trait TokenStore {
+    /// All stored tokens of all guilds.
+    async fn list(&self) -> Result<Vec<StoredToken>, Error>;
+
+    /// Add a token. Fails if there already is a token with the same name.
+    #[cfg_attr(not(feature = "database"), allow(dead_code))]
+    async fn insert(&self, token: StoredToken) -> Result<(), Error>;
+
+    /// Find a token that can be used in a guild by its exact name or address, ignoring capitals.
+    async fn lookup(
+        &self,
+        query: &str,
+        guildid: Option<GuildId>,
+    ) -> Result<Option<StoredToken>, Error> {
+        let query = query.trim();
+        Ok(self.list().await?.into_iter().find(|token| {
+            token.isvisible(guildid)
+                && (token.name.eq_ignore_ascii_case(query)
+                    || token.address.eq_ignore_ascii_case(query))
+        }))
+    }
+
+    /// Find a token that can be used in a guild by its exact address, alias or symbol, without reading the storage.
+    fn find(&self, query: &str, guildid: Option<GuildId>) -> Option<IndexedToken>;
+
+    /// Find the tokens that match a partial query, best matches first, without reading the storage.
+    fn search(&self, query: &str, guildid: Option<GuildId>) -> Vec<IndexedToken>;
+}
+
+#[cfg(feature = "database")]
+pub type Store = SurrealStore;
+#[cfg(not(feature = "database"))]
+pub type Store = FileStore;
+
+/// The token store of this build.
+pub fn store() -> Store {
+    Store {}
+}
+
+/// Tokens stored in the `Coins` table, keyed by their name.
+#[cfg(feature = "database")]
+pub struct SurrealStore {}
+
+#[cfg(feature = "database")]
+impl TokenStore for SurrealStore {
+    async fn list(&self) -> Result<Vec<StoredToken>, Error> {
+        let coins: Vec<crate::commands::price::Coins> = crate::DB.select("Coins").await?;
+        Ok(coins
+            .into_iter()
+            .map(|coin| StoredToken {
+                name: coin.name,
+                address: coin.address,
+                guildid: Some(coin.guildid),
+                global: coin.global,
+                verified: coin.verified,
+                chain: coin.chain,
+            })
+            .collect())
+    }
+
+    async fn insert(&self, token: StoredToken) -> Result<(), Error> {
+        let Some(guildid) = token.guildid else {
+            return Err("Tokens in the database always belong to the guild that added them".into());
+        };
+        let _: Option<crate::commands::price::Coins> = crate::DB
+            .create(("Coins", token.name.clone()))
+            .content(crate::commands::price::Coins {
+                name: token.name,
+                address: token.address,
+                guildid,
+                global: token.global,
+                verified: token.verified,
+                chain: token.chain,
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn lookup(
+        &self,
+        query: &str,
+        guildid: Option<GuildId>,
+    ) -> Result<Option<StoredToken>, Error> {
+        let query = query.trim();
+        // Tokens are keyed by their name, so try that before going over all tokens
+        let coin: Option<crate::commands::price::Coins> =
+            crate::DB.select(("Coins", query.to_string())).await?;
+        if let Some(coin) = coin {
+            let token = StoredToken {
+                name: coin.name,
+                address: coin.address,
+                guildid: Some(coin.guildid),
+                global: coin.global,
+                verified: coin.verified,
+                chain: coin.chain,
+            };
+            if token.isvisible(guildid) {
+                return Ok(Some(token));
+            }
+        }
+        Ok(self.list().await?.into_iter().find(|token| {
+            token.isvisible(guildid)
+                && (token.name.eq_ignore_ascii_case(query)
+                    || token.address.eq_ignore_ascii_case(query))
+        }))
+    }
+
+    // Reading the database on every keystroke is too slow, so the in-memory index of the stored tokens is used
+    fn find(&self, query: &str, guildid: Option<GuildId>) -> Option<IndexedToken> {
+        tokenindex::find(query, guildid)
+    }
+
+    fn search(&self, query: &str, guildid: Option<GuildId>) -> Vec<IndexedToken> {
+        tokenindex::search(query, guildid)
+    }
+}
+
+/// Tokens from the token list file, available in all guilds.
+#[cfg(not(feature = "database"))]
+pub struct FileStore {}
+
+#[cfg(not(feature = "database"))]
+impl TokenStore for FileStore {
+    async fn list(&self) -> Result<Vec<StoredToken>, Error> {
+        Ok(crate::tokenlist::tokens()
+            .into_iter()
+            .map(|token| StoredToken {
+                name: token.name,
+                address: token.address,
+                guildid: None,
+                global: true,
+                verified: false,
+                chain: None,
+            })
+            .collect())
+    }
+
+    async fn insert(&self, token: StoredToken) -> Result<(), Error> {
+        crate::tokenlist::insert(crate::tokenlist::ListedToken {
+            name: token.name,
+            address: token.address,
+        })
+        .await
+    }
+
+    fn find(&self, query: &str, guildid: Option<GuildId>) -> Option<IndexedToken> {
+        tokenindex::find(query, guildid)
+    }
+
+    fn search(&self, query: &str, guildid: Option<GuildId>) -> Vec<IndexedToken> {
+        tokenindex::search(query, guildid)
+    }
+}
+
+/// Tokens kept in memory, used to test commands without a database or token list file.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore {
+    tokens: std::sync::Mutex<Vec<StoredToken>>,
+}
+
+#[cfg(test)]
+impl MemoryStore {
+    pub fn new(tokens: Vec<StoredToken>) -> Self {
+        MemoryStore {
+            tokens: std::sync::Mutex::new(tokens),
+        }
+    }
+
+    fn indexed(&self) -> Vec<IndexedToken> {
+        self.tokens()
+            .iter()
+            .cloned()
+            .map(IndexedToken::from)
+            .collect()
+    }
+
+    fn tokens(&self) -> std::sync::MutexGuard<'_, Vec<StoredToken>> {
+        match self.tokens.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl TokenStore for MemoryStore {
+    async fn list(&self) -> Result<Vec<StoredToken>, Error> {
+        Ok(self.tokens().clone())
+    }
+
+    async fn insert(&self, token: StoredToken) -> Result<(), Error> {
+        let mut tokens = self.tokens();
+        if tokens
+            .iter()
+            .any(|stored| stored.name.eq_ignore_ascii_case(&token.name))
+        {
+            return Err(format!("{} already exists", token.name).into());
+        }
+        tokens.push(token);
+        Ok(())
+    }
+
+    fn find(&self, query: &str, guildid: Option<GuildId>) -> Option<IndexedToken> {
+        tokenindex::findin(&self.indexed(), query, guildid)
+    }
+
+    fn search(&self, query: &str, guildid: Option<GuildId>) -> Vec<IndexedToken> {
+        tokenindex::rank(&self.indexed(), query, guildid, |_| 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(name: &str, address: &str, guildid: Option<u64>, global: bool) -> StoredToken {
+        StoredToken {
+            name: name.to_string(),
+            address: address.to_string(),
+            guildid: guildid.map(GuildId::new),
+            global,
+            verified: false,
+            chain: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn insertrefusesduplicatenames() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        store
+            .insert(token(
+                "OP",
+                "0x4200000000000000000000000000000000000042",
+                Some(1),
+                false,
+            ))
+            .await?;
+        assert!(store
+            .insert(token(
+                "op",
+                "0x0000000000000000000000000000000000000001",
+                Some(2),
+                false
+            ))
+            .await
+            .is_err());
+        assert_eq!(store.list().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lookuprespectsguilds() -> Result<(), Error> {
+        let store = MemoryStore::new(vec![
+            token(
+                "OPENX",
+                "0xc3864f98f2a61a7caeb95b039d031b4e2f55e0e9",
+                Some(1),
+                false,
+            ),
+            token(
+                "ETH",
+                "0x4200000000000000000000000000000000000006",
+                Some(2),
+                true,
+            ),
+        ]);
+        let guild = Some(GuildId::new(1));
+        let otherguild = Some(GuildId::new(3));
+        assert!(store.lookup("openx", guild).await?.is_some());
+        assert!(store.lookup("openx", otherguild).await?.is_none());
+        assert!(store.lookup(" eth ", otherguild).await?.is_some());
+        assert!(store.lookup("ETH", None).await?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn searchranksprefixfirst() {
+        let store = MemoryStore::new(vec![
+            token(
+                "WETH",
+                "0x4200000000000000000000000000000000000006",
+                None,
+                true,
+            ),
+            token(
+                "ETH",
+                "0x0000000000000000000000000000000000000002",
+                None,
+                true,
+            ),
+            token(
+                "ETHX",
+                "0x0000000000000000000000000000000000000003",
+                None,
+                true,
+            ),
+        ]);
+        let names: Vec<String> = store
+            .search("eth", None)
+            .into_iter()
+            .map(|token| token.alias)
+            .collect();
+        assert_eq!(names, vec!["ETH", "ETHX", "WETH"]);
+        assert!(store.search("btc", None).is_empty());
+    }
+}